@@ -0,0 +1,179 @@
+//! Futures and streams that advance a `ProgressBar`.
+use std::future::Future;
+use std::io::{Stdout, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+use crate::ProgressBar;
+
+pin_project! {
+    /// PbFuture is a future that draws its bar as a spinner while
+    /// it is pending, and finishes the bar once it completes.
+    /// See `ProgressFuture`.
+    pub struct PbFuture<F, T: Write> {
+        #[pin]
+        inner: F,
+        pb: ProgressBar<T>,
+    }
+}
+
+pin_project! {
+    /// PbStream is a stream that increments its bar for every item,
+    /// and finishes the bar once the stream ends.
+    /// See `ProgressStream`.
+    pub struct PbStream<S, T: Write> {
+        #[pin]
+        inner: S,
+        pb: ProgressBar<T>,
+    }
+}
+
+impl<F: Future, T: Write> Future for PbFuture<F, T> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let this = self.project();
+        match this.inner.poll(cx) {
+            Poll::Ready(out) => {
+                this.pb.finish();
+                Poll::Ready(out)
+            }
+            Poll::Pending => {
+                this.pb.tick();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<S: Stream, T: Write> Stream for PbStream<S, T> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let this = self.project();
+        let res = this.inner.poll_next(cx);
+        match res {
+            Poll::Ready(Some(_)) => {
+                this.pb.inc();
+            }
+            Poll::Ready(None) => this.pb.finish(),
+            Poll::Pending => {}
+        }
+        res
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// ProgressFuture attaches a `ProgressBar` to any future.
+///
+/// The spinner only moves when the future is polled,
+/// i.e. when it is woken up by the progress of its work.
+///
+/// # Examples
+///
+/// ```ignore
+/// use pb::{ProgressBar, ProgressFuture};
+///
+/// let mut pb = ProgressBar::new(0);
+/// pb.message("connecting ");
+/// let conn = connect(addr).progress_with(pb).await?;
+/// ```
+pub trait ProgressFuture: Future + Sized {
+    /// Draw a spinner on stdout while the future is pending.
+    fn progress(self) -> PbFuture<Self, Stdout> {
+        self.progress_with(ProgressBar::new(0))
+    }
+
+    /// Draw `pb` while the future is pending, it should have
+    /// a total of 0 to be drawn as a spinner.
+    fn progress_with<T: Write>(self, pb: ProgressBar<T>) -> PbFuture<Self, T> {
+        PbFuture { inner: self, pb }
+    }
+}
+
+impl<F: Future> ProgressFuture for F {}
+
+/// ProgressStream attaches a `ProgressBar` to any stream,
+/// like `PbIter` does for iterators.
+///
+/// # Examples
+///
+/// ```ignore
+/// use pb::{ProgressBar, ProgressStream};
+///
+/// let mut pb = ProgressBar::new(urls.len() as u64);
+/// pb.message("downloading ");
+/// let mut pages = fetch_all(urls).progress_with(pb);
+/// while let Some(page) = pages.next().await {
+///     // ...
+/// }
+/// ```
+pub trait ProgressStream: Stream + Sized {
+    /// Draw a bar on stdout for the items of the stream.
+    /// The total is the upper bound of the `size_hint` of the stream,
+    /// without one the bar is drawn as a spinner.
+    fn progress(self) -> PbStream<Self, Stdout> {
+        let total = self.size_hint().1.unwrap_or(0);
+        self.progress_with(ProgressBar::new(total as u64))
+    }
+
+    /// Draw `pb` for the items of the stream.
+    fn progress_with<T: Write>(self, pb: ProgressBar<T>) -> PbStream<Self, T> {
+        PbStream { inner: self, pb }
+    }
+}
+
+impl<S: Stream> ProgressStream for S {}
+
+#[cfg(test)]
+mod test {
+    use crate::future::{ProgressFuture, ProgressStream};
+    use crate::ProgressBar;
+    use futures_core::Stream;
+    use std::future::{poll_fn, Future};
+    use std::pin::{pin, Pin};
+    use std::task::{Context, Poll, Waker};
+
+    // Iter is a stream of the items of an iterator.
+    struct Iter<I>(I);
+
+    impl<I: Iterator + Unpin> Stream for Iter<I> {
+        type Item = I::Item;
+
+        fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<I::Item>> {
+            Poll::Ready(self.0.next())
+        }
+    }
+
+    #[test]
+    fn future() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut polls = 0;
+        let fut = poll_fn(|_| {
+            polls += 1;
+            if polls < 3 {
+                Poll::Pending
+            } else {
+                Poll::Ready(42)
+            }
+        });
+        let mut fut = pin!(fut.progress_with(ProgressBar::on(Vec::new(), 0)));
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(42));
+        assert!(fut.pb.is_finish);
+    }
+
+    #[test]
+    fn stream() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut stream = pin!(Iter(0..3).progress_with(ProgressBar::on(Vec::new(), 3)));
+        while let Poll::Ready(Some(_)) = stream.as_mut().poll_next(&mut cx) {}
+        assert_eq!(stream.pb.current, 3);
+        assert!(stream.pb.is_finish);
+    }
+}
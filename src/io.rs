@@ -0,0 +1,294 @@
+//! Readers and writers that advance a `ProgressBar`
+//! with the number of bytes that pass through them.
+use std::io::{BufRead, Read, Result, Seek, SeekFrom, Stdout, Write};
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use std::pin::Pin;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use std::task::{Context, Poll};
+use crate::ProgressBar;
+
+/// ProgressReader wraps a reader, and adds the number of bytes
+/// read from it to its `ProgressBar`.
+/// Seeking sets the bar to the new position.
+///
+/// With the `tokio` or `futures` features, it wraps the `AsyncRead`
+/// readers of these crates as well.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::fs::File;
+/// use std::io::{self, BufRead, BufReader};
+/// use pb::{ProgressBar, ProgressReader, Units};
+///
+/// let file = File::open("access.log")?;
+/// let mut pb = ProgressBar::new(file.metadata()?.len());
+/// pb.set_units(Units::Bytes);
+/// let mut reader = ProgressReader::new(BufReader::new(file), pb);
+/// let mut errors = 0;
+/// for line in (&mut reader).lines() {
+///     if line?.contains(" 500 ") {
+///         errors += 1;
+///     }
+/// }
+/// reader.progress_bar().finish_print(&format!("{} errors", errors));
+/// # Ok::<(), io::Error>(())
+/// ```
+pub struct ProgressReader<R, T: Write = Stdout> {
+    inner: R,
+    pb: ProgressBar<T>,
+}
+
+/// ProgressWriter wraps a writer, and adds the number of bytes
+/// written to it to its `ProgressBar`.
+/// Seeking sets the bar to the new position.
+///
+/// With the `tokio` or `futures` features, it wraps the `AsyncWrite`
+/// writers of these crates as well.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::fs::File;
+/// use std::io::{self, copy};
+/// use pb::{ProgressBar, ProgressWriter, Units};
+///
+/// let mut file = File::open("/usr/share/dict/words")?;
+/// let mut pb = ProgressBar::new(file.metadata()?.len());
+/// pb.set_units(Units::Bytes);
+/// let mut writer = ProgressWriter::new(File::create("copy-words")?, pb);
+/// copy(&mut file, &mut writer)?;
+/// writer.progress_bar().finish_print("done");
+/// # Ok::<(), io::Error>(())
+/// ```
+pub struct ProgressWriter<W, T: Write = Stdout> {
+    inner: W,
+    pb: ProgressBar<T>,
+}
+
+macro_rules! wrapper {
+    ($name:ident, $inner:ident) => {
+        impl<$inner, T: Write> $name<$inner, T> {
+            /// Wrap `inner`, and advance `pb` with the bytes that pass through it.
+            pub fn new(inner: $inner, pb: ProgressBar<T>) -> Self {
+                $name { inner, pb }
+            }
+
+            /// Get a reference to the wrapped value.
+            pub fn get_ref(&self) -> &$inner {
+                &self.inner
+            }
+
+            /// Get a mutable reference to the wrapped value.
+            /// The bytes that pass through it directly are not counted.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                &mut self.inner
+            }
+
+            /// Get the progress bar, i.e. to set its message or finish it.
+            pub fn progress_bar(&mut self) -> &mut ProgressBar<T> {
+                &mut self.pb
+            }
+
+            /// Unwrap the wrapped value and the progress bar.
+            pub fn into_inner(self) -> ($inner, ProgressBar<T>) {
+                (self.inner, self.pb)
+            }
+        }
+    };
+}
+
+wrapper!(ProgressReader, R);
+wrapper!(ProgressWriter, W);
+
+impl<R: Read, T: Write> Read for ProgressReader<R, T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.pb.add(n as u64);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead, T: Write> BufRead for ProgressReader<R, T> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    // the bytes are counted once they are consumed.
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.pb.add(amt as u64);
+    }
+}
+
+impl<W: Write, T: Write> Write for ProgressWriter<W, T> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.pb.add(n as u64);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+macro_rules! seek {
+    ($name:ident, $inner:ident) => {
+        impl<$inner: Seek, T: Write> Seek for $name<$inner, T> {
+            fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+                let pos = self.inner.seek(pos)?;
+                self.pb.set(pos);
+                Ok(pos)
+            }
+        }
+    };
+}
+
+seek!(ProgressReader, R);
+seek!(ProgressWriter, W);
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin, T: Write + Unpin> tokio::io::AsyncRead
+    for ProgressReader<R, T>
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        let res = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = res {
+            this.pb.add((buf.filled().len() - filled) as u64);
+        }
+        res
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W: tokio::io::AsyncWrite + Unpin, T: Write + Unpin> tokio::io::AsyncWrite
+    for ProgressWriter<W, T>
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.get_mut();
+        let res = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = res {
+            this.pb.add(n as u64);
+        }
+        res
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<R: futures_io::AsyncRead + Unpin, T: Write + Unpin> futures_io::AsyncRead
+    for ProgressReader<R, T>
+{
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let this = self.get_mut();
+        let res = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = res {
+            this.pb.add(n as u64);
+        }
+        res
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<W: futures_io::AsyncWrite + Unpin, T: Write + Unpin> futures_io::AsyncWrite
+    for ProgressWriter<W, T>
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        let this = self.get_mut();
+        let res = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = res {
+            this.pb.add(n as u64);
+        }
+        res
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::{ProgressReader, ProgressWriter};
+    use crate::ProgressBar;
+    use std::io::{copy, BufRead, Cursor, Seek, SeekFrom};
+
+    #[test]
+    fn copy_through() {
+        let mut reader = ProgressReader::new(&b"hello world"[..], ProgressBar::on(Vec::new(), 11));
+        let mut writer = ProgressWriter::new(Vec::new(), ProgressBar::on(Vec::new(), 11));
+        copy(&mut reader, &mut writer).unwrap();
+
+        assert_eq!(reader.progress_bar().current, 11);
+        let (out, pb) = writer.into_inner();
+        assert_eq!(out, b"hello world");
+        assert_eq!(pb.current, 11);
+    }
+
+    #[test]
+    fn buf_read_seek() {
+        let inner = Cursor::new(&b"first\nsecond\n"[..]);
+        let mut reader = ProgressReader::new(inner, ProgressBar::on(Vec::new(), 13));
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "first\n");
+        assert_eq!(reader.progress_bar().current, 6);
+
+        reader.seek(SeekFrom::Start(2)).unwrap();
+        assert_eq!(reader.progress_bar().current, 2);
+        assert_eq!((&mut reader).lines().count(), 2);
+        assert_eq!(reader.progress_bar().current, 13);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn tokio_copy() {
+        let mut reader = ProgressReader::new(&b"hello world"[..], ProgressBar::on(Vec::new(), 11));
+        let mut writer = ProgressWriter::new(Vec::new(), ProgressBar::on(Vec::new(), 11));
+        tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+
+        assert_eq!(reader.progress_bar().current, 11);
+        let (out, pb) = writer.into_inner();
+        assert_eq!(out, b"hello world");
+        assert_eq!(pb.current, 11);
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn futures_read_write() {
+        use futures_io::{AsyncRead, AsyncWrite};
+        use std::pin::Pin;
+        use std::task::{Context, Poll, Waker};
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut reader = ProgressReader::new(&b"hello"[..], ProgressBar::on(Vec::new(), 5));
+        let mut buf = [0; 3];
+        let n = Pin::new(&mut reader).poll_read(&mut cx, &mut buf);
+        assert!(matches!(n, Poll::Ready(Ok(3))));
+        assert_eq!(reader.progress_bar().current, 3);
+
+        let mut writer = ProgressWriter::new(Vec::new(), ProgressBar::on(Vec::new(), 5));
+        let n = Pin::new(&mut writer).poll_write(&mut cx, b"hello");
+        assert!(matches!(n, Poll::Ready(Ok(5))));
+        assert_eq!(writer.get_ref(), b"hello");
+        assert_eq!(writer.progress_bar().current, 5);
+    }
+}
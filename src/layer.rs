@@ -0,0 +1,189 @@
+//! Integration with `tracing` through a `tracing_subscriber::Layer`.
+//!
+//! Enabled with the `tracing` cargo feature.
+
+use crate::{MultiBar, Pipe, ProgressBar};
+use std::fmt::{self, Write as _};
+use std::io::Write;
+use std::sync::Arc;
+use tracing_core::field::{Field, Visit};
+use tracing_core::span::{Attributes, Id, Record};
+use tracing_core::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+// The name of the field that drives the progress bars.
+const PROGRESS_FIELD: &str = "progress";
+
+// SpanBar is the progress bar stored in the extensions of its span.
+struct SpanBar(ProgressBar<Pipe>);
+
+/// ProgressLayer shows a progress bar in a `MultiBar`
+/// for every span that has a `progress` field.
+///
+/// - On a span, the `progress` field sets the total of the span bar.
+/// - On an event, the `progress` field advances the bar
+///   of the closest enclosing span that has one.
+/// - The span bar is finished when its span is closed.
+///
+/// All other events are formatted and printed above the bars.
+///
+/// Spans come and go, so the MultiBar should be started
+/// with `MultiBar::spawn` rather than `MultiBar::listen`,
+/// which returns once all the current bars are finished.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::thread;
+/// use pb::{MultiBar, ProgressLayer};
+/// use tracing_subscriber::prelude::*;
+///
+/// let mb = Arc::new(MultiBar::new());
+/// tracing_subscriber::registry()
+///     .with(ProgressLayer::new(mb.clone()))
+///     .init();
+/// mb.spawn();
+///
+/// let worker = thread::spawn(move || {
+///     let span = tracing::info_span!("download", progress = 100);
+///     let _enter = span.enter();
+///     for _ in 0..100 {
+///         tracing::info!(progress = 1);
+///     }
+///     tracing::info!("download done");
+/// });
+///
+/// worker.join().unwrap();
+/// mb.join();
+/// ```
+pub struct ProgressLayer<T: Write> {
+    mb: Arc<MultiBar<T>>,
+}
+
+impl<T: Write> ProgressLayer<T> {
+    /// Create a new ProgressLayer that draws its bars in `mb`.
+    pub fn new(mb: Arc<MultiBar<T>>) -> Self {
+        ProgressLayer { mb }
+    }
+}
+
+impl<S, T> Layer<S> for ProgressLayer<T>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    T: Write + Send + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(total), Some(span)) = (visitor.progress, ctx.span(id)) {
+            let mut bar = self.mb.create_bar(total);
+            bar.message(&format!("{} ", attrs.metadata().name()));
+            span.extensions_mut().insert(SpanBar(bar));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        values.record(&mut visitor);
+        if let (Some(total), Some(span)) = (visitor.progress, ctx.span(id)) {
+            if let Some(SpanBar(bar)) = span.extensions_mut().get_mut::<SpanBar>() {
+                bar.total = total;
+                bar.add(0);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        if let Some(n) = visitor.progress {
+            if let Some(scope) = ctx.event_scope(event) {
+                for span in scope {
+                    if let Some(SpanBar(bar)) = span.extensions_mut().get_mut::<SpanBar>() {
+                        bar.add(n);
+                        return;
+                    }
+                }
+            }
+            return;
+        }
+
+        let meta = event.metadata();
+        let mut line = format!("{:>5} {}: {}", meta.level(), meta.target(), visitor.message);
+        line += &visitor.fields;
+        self.mb.println_above(&line);
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            if let Some(SpanBar(mut bar)) = span.extensions_mut().remove::<SpanBar>() {
+                bar.finish();
+            }
+        }
+    }
+}
+
+// FieldVisitor extracts the progress field,
+// and formats the rest of the fields.
+#[derive(Default)]
+struct FieldVisitor {
+    progress: Option<u64>,
+    message: String,
+    fields: String,
+}
+
+impl Visit for FieldVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == PROGRESS_FIELD {
+            self.progress = Some(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if field.name() == PROGRESS_FIELD && value >= 0 {
+            self.progress = Some(value as u64);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::layer::ProgressLayer;
+    use crate::MultiBar;
+    use std::sync::Arc;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn span_bar() {
+        let mb = Arc::new(MultiBar::on(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(ProgressLayer::new(mb.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("copy", progress = 2);
+            let _enter = span.enter();
+            tracing::info!(progress = 1);
+            mb.step();
+            tracing::info!(file = "a.txt", "copied");
+            tracing::info!(progress = 1);
+        });
+        mb.listen();
+
+        let out = mb.output();
+        assert!(out.contains("\r INFO pb::layer::test: copied file=\"a.txt\""));
+        assert!(out.contains("copy 1 / 2 "));
+        assert!(out.contains("copy 2 / 2 "));
+    }
+}
@@ -6,7 +6,7 @@
 //! 1. simple example
 //!
 //! ```ignore
-//! use pbr::ProgressBar;
+//! use pb::ProgressBar;
 //! use std::thread;
 //!
 //! fn main() {
@@ -23,7 +23,7 @@
 //!
 //! ```ignore
 //! use std::thread;
-//! use pbr::MultiBar;
+//! use pb::MultiBar;
 //! use std::time::Duration;
 //!
 //! fn main() {
@@ -60,23 +60,21 @@
 //! }
 //! ```
 //!
-//! 3. Broadcast writing(simple file copying)
+//! 3. Passthrough writing(simple file copying)
 //!
-//! ```ignore
-//! #![feature(io)]
+//! ```no_run
 //! use std::io::copy;
-//! use std::io::prelude::*;
 //! use std::fs::File;
-//! use pbr::{ProgressBar, Units};
+//! use pb::{ProgressBar, ProgressWriter, Units};
 //!
 //! fn main() {
 //!     let mut file = File::open("/usr/share/dict/words").unwrap();
-//!     let n_bytes = file.metadata().unwrap().len() as usize;
+//!     let n_bytes = file.metadata().unwrap().len();
 //!     let mut pb = ProgressBar::new(n_bytes);
 //!     pb.set_units(Units::Bytes);
-//!     let mut handle = File::create("copy-words").unwrap().broadcast(&mut pb);
+//!     let mut handle = ProgressWriter::new(File::create("copy-words").unwrap(), pb);
 //!     copy(&mut file, &mut handle).unwrap();
-//!     pb.finish_print("done");
+//!     handle.progress_bar().finish_print("done");
 //! }
 //! ```
 // Macro for writing to the giving writer.
@@ -99,12 +97,36 @@ macro_rules! printfl {
 
 mod pb;
 mod tty;
+mod tree;
 mod multi;
-pub use multi::{MultiBar, Pipe};
-pub use pb::{ProgressBar, Units};
+mod io;
+#[cfg(feature = "futures")]
+mod future;
+#[cfg(feature = "log")]
+mod logger;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "tracing")]
+mod layer;
+pub use io::{ProgressReader, ProgressWriter};
+pub use multi::{Anchor, MultiBar, Pipe, Stats, SummaryPosition, TextLine};
+pub use pb::{ProgressBar, Snapshot, Units};
+pub use tree::ParentBar;
+#[cfg(feature = "futures")]
+pub use future::{PbFuture, PbStream, ProgressFuture, ProgressStream};
+#[cfg(feature = "log")]
+pub use logger::{LogTarget, LogWrapper};
+#[cfg(feature = "tracing")]
+pub use layer::ProgressLayer;
+#[cfg(feature = "tokio")]
+pub use multi::Frames;
+#[cfg(feature = "rayon")]
+pub use par::{ParallelProgressIterator, PbParIter};
 use std::io::{stdout, Stdout, Write};
+use std::iter::FusedIterator;
 
-
+/// PbIter wraps an iterator, and increments its `ProgressBar` for every item.
+/// See also `ProgressIterator`.
 pub struct PbIter<T, I>
 where
 I: Iterator,
@@ -119,12 +141,59 @@ where
 I: Iterator,
 T: Write,
 {
+    /// Create a new PbIter with a bar on an arbitrary writer.
+    /// The total of the bar is the upper bound of the `size_hint` of `iter`,
+    /// which is its length for an `ExactSizeIterator`.
+    /// Without an upper bound the bar is drawn as a spinner.
     pub fn on(handle: T, iter: I) -> Self {
-        let size = iter.size_hint().0;
-        PbIter {
-            iter,
-            progress_bar: ProgressBar::on(handle, size as u64),
+        let size = iter.size_hint().1.unwrap_or(0);
+        Self::with_bar(iter, ProgressBar::on(handle, size as u64))
+    }
+
+    /// Create a new PbIter with a preconfigured bar,
+    /// i.e. with its own units, format, message or refresh rate,
+    /// or a bar of a `MultiBar`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::thread;
+    /// use pb::{MultiBar, PbIter};
+    ///
+    /// let mb = MultiBar::new();
+    /// let mut pb = mb.create_bar(100);
+    /// pb.message("items ");
+    /// pb.show_speed = false;
+    /// thread::spawn(move || {
+    ///     for _ in PbIter::with_bar(0..100, pb) {
+    ///         // ...
+    ///     }
+    /// });
+    /// mb.listen();
+    /// ```
+    pub fn with_bar(iter: I, progress_bar: ProgressBar<T>) -> Self {
+        PbIter { iter, progress_bar }
+    }
+
+    /// Get the progress bar, i.e. to set its message while iterating.
+    pub fn progress_bar(&mut self) -> &mut ProgressBar<T> {
+        &mut self.progress_bar
+    }
+
+    // consumed adds `n` consumed items to the bar if there is an `item`,
+    // otherwise the iterator is exhausted and the bar is finished.
+    fn consumed(&mut self, item: Option<I::Item>, n: u64) -> Option<I::Item> {
+        match item {
+            Some(_) => {
+                self.progress_bar.add(n);
+            }
+            None => {
+                if !self.progress_bar.is_finish {
+                    self.progress_bar.finish();
+                }
+            }
         }
+        item
     }
 }
 
@@ -145,16 +214,188 @@ T: Write,
     type Item = I::Item;
     
     fn next(&mut self) -> Option<I::Item> {
-        match self.iter.next() {
-            Some(i) => {
-                self.progress_bar.inc();
-                Some(i)
-            }
-            None => None,
+        let item = self.iter.next();
+        self.consumed(item, 1)
+    }
+
+    // the skipped items are counted as well.
+    fn nth(&mut self, n: usize) -> Option<I::Item> {
+        let item = self.iter.nth(n);
+        self.consumed(item, n as u64 + 1)
+    }
+
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, I::Item) -> B,
+    {
+        let PbIter { iter, mut progress_bar } = self;
+        let acc = iter.fold(init, |acc, item| {
+            progress_bar.inc();
+            f(acc, item)
+        });
+        if !progress_bar.is_finish {
+            progress_bar.finish();
         }
+        acc
     }
     
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.iter.size_hint()
     }
-}
\ No newline at end of file
+}
+
+impl<T, I> DoubleEndedIterator for PbIter<T, I>
+where
+I: DoubleEndedIterator,
+T: Write,
+{
+    fn next_back(&mut self) -> Option<I::Item> {
+        let item = self.iter.next_back();
+        self.consumed(item, 1)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<I::Item> {
+        let item = self.iter.nth_back(n);
+        self.consumed(item, n as u64 + 1)
+    }
+
+    fn rfold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, I::Item) -> B,
+    {
+        let PbIter { iter, mut progress_bar } = self;
+        let acc = iter.rfold(init, |acc, item| {
+            progress_bar.inc();
+            f(acc, item)
+        });
+        if !progress_bar.is_finish {
+            progress_bar.finish();
+        }
+        acc
+    }
+}
+
+impl<T, I> ExactSizeIterator for PbIter<T, I>
+where
+I: ExactSizeIterator,
+T: Write,
+{
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<T, I> FusedIterator for PbIter<T, I>
+where
+I: FusedIterator,
+T: Write,
+{
+}
+
+/// ProgressIterator attaches a `ProgressBar` to any iterator,
+/// the bar is incremented for every item and finished
+/// once the iterator is exhausted.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pb::{ProgressBar, ProgressIterator};
+///
+/// // the total is the length of the iterator.
+/// for _ in (0..100).progress() {
+///     // ...
+/// }
+///
+/// // a filtered iterator uses the length of the iterator it filters.
+/// let even: Vec<_> = (0..100).filter(|n| n % 2 == 0).progress().collect();
+///
+/// // a preconfigured bar, with its own total.
+/// let mut pb = ProgressBar::new(10);
+/// pb.message("lines ");
+/// for _ in std::io::stdin().lines().progress_with(pb) {
+///     // ...
+/// }
+/// ```
+pub trait ProgressIterator: Iterator + Sized {
+    /// Draw a bar on stdout, see `PbIter::on` for its total.
+    fn progress(self) -> PbIter<Stdout, Self> {
+        PbIter::new(self)
+    }
+
+    /// Draw a bar on stdout with the total `n`,
+    /// for iterators without a known length.
+    fn progress_count(self, n: u64) -> PbIter<Stdout, Self> {
+        self.progress_with(ProgressBar::new(n))
+    }
+
+    /// Draw the bar `pb`, including a bar of a `MultiBar`.
+    fn progress_with<T: Write>(self, pb: ProgressBar<T>) -> PbIter<T, Self> {
+        PbIter::with_bar(self, pb)
+    }
+}
+
+impl<I: Iterator> ProgressIterator for I {}
+
+#[cfg(test)]
+mod test {
+    use crate::{MultiBar, PbIter, ProgressBar, ProgressIterator};
+
+    #[test]
+    fn progress_total() {
+        let it = (0..10).filter(|n| n % 2 == 0).progress();
+        assert_eq!(it.progress_bar.total, 10);
+        let it = std::iter::repeat(1).progress();
+        assert_eq!(it.progress_bar.total, 0);
+    }
+
+    #[test]
+    fn double_ended() {
+        let mut it = (0..10).progress_with(ProgressBar::on(Vec::new(), 10));
+        assert_eq!(it.len(), 10);
+        assert_eq!(it.next_back(), Some(9));
+        assert_eq!(it.nth(2), Some(2));
+        assert_eq!(it.progress_bar().current, 4);
+        assert_eq!(it.len(), 6);
+
+        let sum: i32 = it.by_ref().rev().sum();
+        assert_eq!(sum, 3 + 4 + 5 + 6 + 7 + 8);
+        assert_eq!(it.progress_bar().current, 10);
+    }
+
+    #[test]
+    fn fold() {
+        let mut out = Vec::new();
+        let mut pb = ProgressBar::on(&mut out, 10);
+        pb.show_speed = false;
+        let mut items = Vec::new();
+        (0..5).progress_with(pb).rev().for_each(|n| items.push(n));
+        assert_eq!(items, vec![4, 3, 2, 1, 0]);
+
+        // the bar is finished, even though there were less items.
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("5 / 10 "));
+        assert!(out.contains("10 / 10 "));
+    }
+
+    #[test]
+    fn with_multibar() {
+        let mb = MultiBar::on(Vec::new());
+        let mut pb = mb.create_bar(3);
+        pb.message("items ");
+        assert_eq!(PbIter::with_bar("abc".chars(), pb).count(), 3);
+        mb.listen();
+
+        assert_eq!(mb.stats().finished, 1);
+        assert!(mb.output().contains("items 3 / 3 "));
+    }
+
+    #[test]
+    fn progress_finish() {
+        let mut it = (0..3).progress_with(ProgressBar::on(Vec::new(), 3));
+        assert_eq!(it.by_ref().count(), 3);
+        assert_eq!(it.next(), None);
+        let pb = it.progress_bar();
+        assert!(pb.is_finish);
+        assert_eq!(pb.current, 3);
+    }
+}
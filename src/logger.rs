@@ -0,0 +1,177 @@
+//! Integration with the `log` crate facade.
+//!
+//! Enabled with the `log` cargo feature.
+
+use crate::pb::is_suspended;
+use crate::{MultiBar, ProgressBar};
+use log::{Log, Metadata, Record, SetLoggerError};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// LogTarget is implemented by bars that can be cleared
+/// from the screen while a log record is written.
+pub trait LogTarget {
+    /// Clear the bars, call `f` and redraw the bars below its output.
+    fn with_suspended(&self, f: &mut dyn FnMut());
+}
+
+impl<T: Write> LogTarget for MultiBar<T> {
+    fn with_suspended(&self, f: &mut dyn FnMut()) {
+        self.suspend(f)
+    }
+}
+
+impl<T: Write> LogTarget for Mutex<ProgressBar<T>> {
+    fn with_suspended(&self, f: &mut dyn FnMut()) {
+        self.lock().unwrap().suspend(f)
+    }
+}
+
+impl<B: LogTarget + ?Sized> LogTarget for Arc<B> {
+    fn with_suspended(&self, f: &mut dyn FnMut()) {
+        (**self).with_suspended(f)
+    }
+}
+
+/// LogWrapper wraps any `log::Log` implementation,
+/// so that the records it writes are printed above the live bars
+/// instead of being interleaved with them.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use pb::{LogWrapper, MultiBar};
+/// # struct SimpleLogger;
+/// # impl log::Log for SimpleLogger {
+/// #     fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #     fn log(&self, r: &log::Record) { eprintln!("{}", r.args()) }
+/// #     fn flush(&self) {}
+/// # }
+///
+/// let mb = Arc::new(MultiBar::new());
+/// LogWrapper::new(mb.clone(), SimpleLogger).try_init().unwrap();
+/// log::set_max_level(log::LevelFilter::Info);
+///
+/// let mut p1 = mb.create_bar(10);
+/// std::thread::spawn(move || {
+///     for i in 0..10 {
+///         log::info!("processing item {}", i);
+///         p1.inc();
+///     }
+///     p1.finish();
+/// });
+///
+/// mb.listen();
+/// ```
+pub struct LogWrapper<B, L> {
+    bars: B,
+    logger: L,
+}
+
+impl<B, L> LogWrapper<B, L>
+where
+    B: LogTarget + Send + Sync,
+    L: Log,
+{
+    /// Create a new LogWrapper that routes the records
+    /// of `logger` through `bars`.
+    pub fn new(bars: B, logger: L) -> Self {
+        LogWrapper { bars, logger }
+    }
+}
+
+impl<B, L> LogWrapper<B, L>
+where
+    B: LogTarget + Send + Sync + 'static,
+    L: Log + 'static,
+{
+    /// Install the LogWrapper as the global logger.
+    /// The max level should still be set with `log::set_max_level`.
+    pub fn try_init(self) -> Result<(), SetLoggerError> {
+        log::set_boxed_logger(Box::new(self))
+    }
+}
+
+impl<B, L> Log for LogWrapper<B, L>
+where
+    B: LogTarget + Send + Sync,
+    L: Log,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.logger.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.logger.enabled(record.metadata()) {
+            return;
+        }
+        // the bars are already suspended, and locked, by this thread.
+        if is_suspended() {
+            self.logger.log(record);
+            self.logger.flush();
+        } else {
+            self.bars.with_suspended(&mut || {
+                self.logger.log(record);
+                self.logger.flush();
+            });
+        }
+    }
+
+    fn flush(&self) {
+        self.logger.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::logger::LogWrapper;
+    use crate::ProgressBar;
+    use log::{Level, Log, Metadata, Record};
+    use std::sync::{Arc, Mutex};
+
+    // VecLogger collects the records up to the info level.
+    struct VecLogger(Mutex<Vec<String>>);
+
+    impl Log for VecLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= Level::Info
+        }
+
+        fn log(&self, record: &Record) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn log_filters_records() {
+        let mut out = Vec::new();
+        {
+            let mut pb = ProgressBar::on(&mut out, 10);
+            pb.set_width(Some(40));
+            let logger = LogWrapper::new(Mutex::new(pb), VecLogger(Mutex::new(Vec::new())));
+            let record = |level, args| Record::builder().level(level).args(args).build();
+            assert!(logger.enabled(&Metadata::builder().level(Level::Warn).build()));
+            assert!(!logger.enabled(&Metadata::builder().level(Level::Debug).build()));
+
+            logger.log(&record(Level::Info, format_args!("hello")));
+            logger.log(&record(Level::Debug, format_args!("hidden")));
+            assert_eq!(*logger.logger.0.lock().unwrap(), vec!["hello"]);
+        }
+        // the bar is only cleared for the records that are written.
+        let blank = format!("\r{}\r", " ".repeat(40));
+        assert_eq!(std::str::from_utf8(&out).unwrap().matches(&blank).count(), 1);
+    }
+
+    #[test]
+    fn log_inside_suspend() {
+        let pb = Arc::new(Mutex::new(ProgressBar::on(Vec::new(), 10)));
+        let logger = LogWrapper::new(pb.clone(), VecLogger(Mutex::new(Vec::new())));
+        let record = Record::builder().args(format_args!("nested")).build();
+        // the bar is locked by `suspend`, so the record is written as is.
+        pb.lock().unwrap().suspend(|| logger.log(&record));
+        assert_eq!(*logger.logger.0.lock().unwrap(), vec!["nested"]);
+    }
+}
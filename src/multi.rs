@@ -1,29 +1,442 @@
-use std::sync::Mutex;
+use std::thread;
+use std::sync::{Arc, Mutex};
+use crate::pb::{suspended, Progress, Style};
 use crate::ProgressBar;
-use std::str::from_utf8;
-use crate::tty::move_cursor_up;
-use std::io::{Write, Result, Stdout};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use crossbeam_channel::{Receiver, Sender, unbounded};
+use std::thread::{JoinHandle, ThreadId};
+use crate::tty::{move_cursor_up, redraw_line, terminal_size, Height};
+use std::time::{Duration, Instant};
+use std::fmt;
+use std::io::{Sink, Write, Result, Stdout};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use crossbeam_channel::{Receiver, RecvTimeoutError, SendError, Sender, bounded, unbounded};
+#[cfg(feature = "tokio")]
+use atomic_waker::AtomicWaker;
+
+#[cfg(feature = "tokio")]
+mod stream;
+#[cfg(feature = "tokio")]
+pub use self::stream::Frames;
+
+// Line is either a bar or a text line.
+// Lines are identified by id, because their
+// position changes as lines are inserted and removed.
+struct Line {
+    id: usize,
+    // text is the content of a text line, or of a bar
+    // that is finished or replaced by `finish_print`.
+    text: Option<String>,
+    // bar is the last state of a bar, `None` for text lines.
+    bar: Option<ProgressBar<Sink>>,
+    // is_text is set for the text lines created by `println`.
+    is_text: bool,
+    done: bool,
+    // depth is the nesting level of a child bar, 0 for top level lines.
+    depth: usize,
+}
+
+impl Line {
+    // render returns the content of the line at the given time.
+    fn render(&mut self, now: Instant) -> String {
+        match (&self.text, &mut self.bar) {
+            (Some(text), _) => text.clone(),
+            (None, Some(bar)) => bar.line(now),
+            // the bar was not drawn yet
+            (None, None) => String::new(),
+        }
+    }
+
+    // finish marks the line as done, and freezes the last state of the bar.
+    fn finish(&mut self, now: Instant) {
+        self.done = true;
+        if self.text.is_none() {
+            self.text = Some(self.render(now));
+        }
+    }
+}
+
+/// The position of the summary bar of a `MultiBar`,
+/// see `MultiBar::set_summary`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryPosition {
+    Top,
+    Bottom,
+}
+
+/// The line of the terminal a `MultiBar` stays at,
+/// see `MultiBar::set_anchor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    /// The first line stays in place, and the lines below it
+    /// are cleared when there are less lines to draw.
+    Top,
+    /// The last line stays in place, and the lines above it are
+    /// left blank when there are less lines to draw, until they
+    /// are filled by the next log lines.
+    Bottom,
+}
+
+// Summary is the bar that aggregates the progress of all bars.
+struct Summary {
+    bar: ProgressBar<Sink>,
+    position: SummaryPosition,
+}
+
+/// Stats are the numbers of bars of a `MultiBar` by their state,
+/// see `MultiBar::stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of bars created so far.
+    pub created: usize,
+    /// The number of bars that called `finish`.
+    pub finished: usize,
+    /// The number of bars that were dropped or removed before they finished.
+    pub abandoned: usize,
+    /// The number of bars that are still running.
+    pub in_progress: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} in progress, {} finished, {} abandoned",
+            self.in_progress, self.finished, self.abandoned
+        )
+    }
+}
+
+// Counters counts the bars of a MultiBar by their state.
+// The bars update it directly, so it is always up to date.
+#[derive(Default)]
+struct Counters {
+    created: AtomicUsize,
+    finished: AtomicUsize,
+    abandoned: AtomicUsize,
+}
+
+impl Counters {
+    fn stats(&self) -> Stats {
+        let created = self.created.load(Ordering::SeqCst);
+        let finished = self.finished.load(Ordering::SeqCst);
+        let abandoned = self.abandoned.load(Ordering::SeqCst);
+        Stats {
+            created,
+            finished,
+            abandoned,
+            in_progress: created.saturating_sub(finished + abandoned),
+        }
+    }
+}
+
+// FRAME_INTERVAL is the default minimum time between two frames.
+const FRAME_INTERVAL: Duration = Duration::from_millis(50);
+
+// INDENT is the indentation of every nesting level of child bars.
+pub(crate) const INDENT: usize = 2;
 
 struct State<T: Write> {
-    lines: Vec<String>,
+    lines: Vec<Line>,
     nlines: usize,
+    // drawn is the number of lines that are currently on the screen.
+    drawn: usize,
+    // screen is the lines of the last frame, so only what changed is redrawn.
+    screen: Vec<String>,
+    // max_width is the width of the widest line drawn so far,
+    // used to clear the lines that are left over from the previous frame.
+    max_width: usize,
+    // max_lines is the maximum number of lines to draw,
+    // `None` means the height of the terminal.
+    max_lines: Option<usize>,
+    summary: Option<Summary>,
+    // status is the counters to draw as a status line, if it is enabled.
+    status: Option<Arc<Counters>>,
+    anchor: Anchor,
+    // frame_interval is the minimum time between two frames.
+    frame_interval: Duration,
     handle: T,
 }
 
+impl<T: Write> State<T> {
+    // position returns the index of the line with the given id.
+    fn position(&self, id: usize) -> Option<usize> {
+        self.lines.iter().position(|l| l.id == id)
+    }
+
+    // insert inserts a new line with the given id at `index`.
+    fn insert(&mut self, index: usize, id: usize, text: Option<String>, depth: usize) {
+        self.lines.insert(
+            index,
+            Line {
+                id,
+                is_text: text.is_some(),
+                text,
+                bar: None,
+                done: false,
+                depth,
+            },
+        );
+        self.nlines += 1;
+    }
+
+    // insert_child inserts a new line below the last descendant
+    // of the line with the id `parent`.
+    fn insert_child(&mut self, parent: usize, id: usize) {
+        let (index, depth) = match self.position(parent) {
+            Some(i) => {
+                let depth = self.lines[i].depth;
+                let end = self.lines[i + 1..]
+                    .iter()
+                    .position(|l| l.depth <= depth)
+                    .map_or(self.nlines, |n| i + 1 + n);
+                (end, depth + 1)
+            }
+            // the parent was removed
+            None => (self.nlines, 0),
+        };
+        self.insert(index, id, None, depth);
+    }
+
+    // visible returns the indexes of the lines to draw,
+    // and an overflow line for the hidden bars, if there are any.
+    // Text lines are always drawn. Unfinished bars are preferred over
+    // finished bars, and recent bars are preferred over old ones.
+    // A child bar is only drawn together with its parent bars.
+    fn visible(&self) -> (Vec<usize>, Option<String>) {
+        let max_lines = self.max_lines.or_else(|| {
+            // the cursor can't reach the lines that scrolled off the screen.
+            terminal_size().map(|(_, Height(h))| (h as usize).saturating_sub(1))
+        });
+        // leave room for the summary bar and the status line
+        let extra = self.summary.is_some() as usize + self.status.is_some() as usize;
+        let max_lines = max_lines.map(|n| n.saturating_sub(extra));
+        let max_lines = match max_lines {
+            Some(n) if n < self.nlines => n,
+            _ => return ((0..self.nlines).collect(), None),
+        };
+
+        let mut shown: Vec<bool> = self.lines.iter().map(|l| l.is_text).collect();
+        // leave room for the overflow line
+        let texts = shown.iter().filter(|&&s| s).count();
+        let mut room = max_lines.saturating_sub(1 + texts);
+
+        let mut bars: Vec<usize> = (0..self.nlines).filter(|&i| !shown[i]).collect();
+        bars.sort_by_key(|&i| (self.lines[i].done, std::cmp::Reverse(self.lines[i].id)));
+        for &i in bars.iter() {
+            let mut needed = vec![i];
+            needed.extend(self.ancestors(i).into_iter().filter(|&a| !shown[a]));
+            if needed.len() <= room {
+                room -= needed.len();
+                needed.into_iter().for_each(|n| shown[n] = true);
+            }
+        }
+
+        let hidden: Vec<usize> = bars.into_iter().filter(|&i| !shown[i]).collect();
+        let done = hidden.iter().filter(|&&i| self.lines[i].done).count();
+        let overflow = format!("… and {} more ({} done)", hidden.len(), done);
+        let shown = (0..self.nlines).filter(|&i| shown[i]).collect();
+        (shown, Some(overflow))
+    }
+
+    // ancestors returns the indexes of the parent bars of the line at `i`.
+    fn ancestors(&self, i: usize) -> Vec<usize> {
+        let mut depth = self.lines[i].depth;
+        let mut ancestors = Vec::new();
+        for j in (0..i).rev() {
+            if depth == 0 {
+                break;
+            }
+            if self.lines[j].depth < depth {
+                depth = self.lines[j].depth;
+                ancestors.push(j);
+            }
+        }
+        ancestors
+    }
+
+    // summary_line returns the line of the summary bar, if there is one.
+    // Parent bars are skipped, as their children are already counted.
+    fn summary_line(&mut self) -> Option<String> {
+        let summary = self.summary.as_mut()?;
+        let (mut current, mut total) = (0, 0);
+        for (i, l) in self.lines.iter().enumerate() {
+            let parent = self.lines.get(i + 1).is_some_and(|n| n.depth > l.depth);
+            if let (Some(bar), false) = (&l.bar, parent) {
+                current += bar.current;
+                total += bar.total;
+            }
+        }
+        summary.bar.current = current;
+        summary.bar.total = total;
+        Some(summary.bar.line(Instant::now()))
+    }
+
+    // cursor_up returns the output that moves the cursor
+    // to the first line that is currently on the screen.
+    fn cursor_up(&self) -> String {
+        if self.drawn > 0 {
+            move_cursor_up(self.drawn)
+        } else {
+            String::new()
+        }
+    }
+
+    // clear returns the output that clears all the lines on the screen,
+    // and leaves the cursor on the first one. The next frame is drawn
+    // from there, like the first frame.
+    fn clear(&mut self) -> String {
+        let up = self.cursor_up();
+        let blank = format!("\r{}\n", " ".repeat(self.max_width));
+        let out = up.clone() + &blank.repeat(self.drawn) + &up;
+        self.drawn = 0;
+        self.screen.clear();
+        out
+    }
+
+    // frame returns the output that draws all lines over the `height`
+    // lines below the cursor, and clears the lines that are left over.
+    // These are the last lines of the previous frame, so only
+    // the lines, or the ends of lines, that changed are redrawn.
+    fn frame(&mut self, height: usize) -> String {
+        let now = Instant::now();
+        let (shown, overflow) = self.visible();
+        let summary = self.summary_line();
+        let top = self.summary.as_ref().map(|s| s.position) == Some(SummaryPosition::Top);
+
+        let mut lines = Vec::new();
+        if let (Some(summary), true) = (&summary, top) {
+            lines.push(summary.clone());
+        }
+        for &i in shown.iter() {
+            let l = &mut self.lines[i];
+            lines.push(" ".repeat(l.depth * INDENT) + &l.render(now));
+        }
+        lines.extend(overflow);
+        if let (Some(summary), false) = (summary, top) {
+            lines.push(summary);
+        }
+        if let Some(counters) = &self.status {
+            lines.push(counters.stats().to_string());
+        }
+
+        if self.anchor == Anchor::Bottom && height > lines.len() {
+            // keep the last line in place, the lines above it
+            // stay blank until the next log lines fill them.
+            let blank = vec![String::new(); height - lines.len()];
+            lines.splice(0..0, blank);
+        }
+
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        self.max_width = self.max_width.max(width);
+        let screen = &self.screen[self.screen.len().saturating_sub(height)..];
+        let mut out = String::new();
+        for (i, l) in lines.iter().enumerate() {
+            out += &redraw_line(screen.get(i).map_or("", |s| s.as_str()), l);
+            out.push('\n');
+        }
+
+        let nlines = lines.len();
+        if height > nlines {
+            for old in screen[nlines..].iter() {
+                out += &redraw_line(old, "");
+                out.push('\n');
+            }
+            out += &move_cursor_up(height - nlines);
+        }
+        self.drawn = nlines;
+        self.screen = lines;
+        out
+    }
+}
+
 // WriteMsg is the message format used for
 // communication between MultiBar and its bars.
-struct WriteMsg {
-    done: bool,
-    level: usize,
-    string: String,
+enum WriteMsg {
+    // Template replaces the copy of the bar with the given id,
+    // that its updates are applied to.
+    Template {
+        id: usize,
+        bar: Box<ProgressBar<Sink>>,
+    },
+    // Update updates the progress of the bar with the given id.
+    Update { id: usize, progress: Progress },
+    // Text replaces the bar with the given id with a text line.
+    Text { id: usize, string: String },
+    // Above prints a line above the bars.
+    Above(String),
+    // Insert inserts a line for the child bar with the given id.
+    Insert { id: usize, parent: usize },
+    // Remove removes the line with the given id.
+    Remove(usize),
+    // Finish notifies that the bar with the given id finished its work.
+    Finish(usize),
+    // Abandon notifies that the bar with the given id
+    // was dropped before it finished its work.
+    Abandon(usize),
+    // Suspend clears the bars and notifies `cleared`,
+    // then no frame is drawn until `resume` is dropped.
+    Suspend {
+        cleared: Sender<()>,
+        resume: Receiver<()>,
+    },
+    // Wake wakes up `listen` to check whether it should stop.
+    Wake,
+}
+
+// Chan is the sending side of the channel of a MultiBar.
+#[derive(Clone)]
+struct Chan {
+    sender: Sender<WriteMsg>,
+    // listener is the thread that receives the messages, if there is one.
+    listener: Arc<Mutex<Option<ThreadId>>>,
+    // waker wakes up `listen_async` when a message is sent.
+    #[cfg(feature = "tokio")]
+    waker: Arc<AtomicWaker>,
+}
+
+impl Chan {
+    fn send(&self, msg: WriteMsg) -> std::result::Result<(), SendError<WriteMsg>> {
+        let res = self.sender.send(msg);
+        #[cfg(feature = "tokio")]
+        self.waker.wake();
+        res
+    }
+
+    // set_listener sets the thread that receives the messages,
+    // or `None` when no thread receives them.
+    fn set_listener(&self, listener: Option<ThreadId>) {
+        *self.listener.lock().unwrap() = listener;
+    }
+
+    // listening returns whether the messages are received
+    // by another thread than the current one.
+    fn listening(&self) -> bool {
+        let listener = *self.listener.lock().unwrap();
+        listener.is_some_and(|id| id != thread::current().id())
+    }
+}
+
+// channel creates the channel of a MultiBar.
+fn channel() -> (Chan, Receiver<WriteMsg>) {
+    let (sender, receiver) = unbounded();
+    let chan = Chan {
+        sender,
+        listener: Arc::default(),
+        #[cfg(feature = "tokio")]
+        waker: Arc::default(),
+    };
+    (chan, receiver)
 }
 
 pub struct MultiBar<T: Write> {
     state: Mutex<State<T>>,
-    chan: (Sender<WriteMsg>, Receiver<WriteMsg>),
-    nbars: AtomicUsize,
+    chan: (Chan, Receiver<WriteMsg>),
+    nbars: Arc<AtomicUsize>,
+    next_id: Arc<AtomicUsize>,
+    counters: Arc<Counters>,
+    // keep_alive keeps `listen` running even if there are no bars,
+    // it is set while the MultiBar is spawned.
+    keep_alive: AtomicBool,
+    thread: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl MultiBar<Stdout> {
@@ -33,7 +446,7 @@ impl MultiBar<Stdout> {
     ///
     /// ```no_run
     /// use std::thread;
-    /// use pbr::MultiBar;
+    /// use pb::MultiBar;
     /// use std::time::Duration;
     ///
     /// let mut mb = MultiBar::new();
@@ -72,13 +485,19 @@ impl MultiBar<Stdout> {
     }
 }
 
+impl Default for MultiBar<Stdout> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Write> MultiBar<T> {
     /// Create a new MultiBar with an arbitrary writer.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use pbr::MultiBar;
+    /// use pb::MultiBar;
     /// use std::io::stderr;
     ///
     /// let mut mb = MultiBar::on(stderr());
@@ -92,9 +511,21 @@ impl<T: Write> MultiBar<T> {
                 lines: Vec::new(),
                 handle,
                 nlines: 0,
+                drawn: 0,
+                screen: Vec::new(),
+                max_width: 0,
+                max_lines: None,
+                summary: None,
+                status: None,
+                anchor: Anchor::Top,
+                frame_interval: FRAME_INTERVAL,
             }),
-            chan: unbounded(),
-            nbars: AtomicUsize::new(0),
+            chan: channel(),
+            nbars: Arc::new(AtomicUsize::new(0)),
+            next_id: Arc::new(AtomicUsize::new(0)),
+            counters: Arc::new(Counters::default()),
+            keep_alive: AtomicBool::new(false),
+            thread: Mutex::new(None),
         }
     }
 
@@ -106,11 +537,14 @@ impl<T: Write> MultiBar<T> {
     /// This is a blocking operation and blocks until all bars will finish.
     /// To ignore blocking, you can run it in a different thread.
     ///
+    /// The bars are drawn at most once per frame interval,
+    /// see `set_frame_interval`.
+    ///
     /// # Examples
     ///
     /// ```no_run
     /// use std::thread;
-    /// use pbr::MultiBar;
+    /// use pb::MultiBar;
     ///
     /// let mut mb = MultiBar::new();
     ///
@@ -126,33 +560,166 @@ impl<T: Write> MultiBar<T> {
     /// // ...
     /// ```
     pub fn listen(&self) {
-        let mut first = true;
-        let mut out = String::new();
-        while self.nbars.load(Ordering::SeqCst) > 0 {
+        self.chan.0.set_listener(Some(thread::current().id()));
+        let mut last_frame: Option<Instant> = None;
+        while self.running() {
             // receive message
             let msg = self.chan.1.recv().unwrap();
-            if msg.done {
-                self.nbars.fetch_sub(1, Ordering::SeqCst);
-                continue;
-            }
+            let mut above = Vec::new();
+            let mut dirty = self.apply(msg, &mut above);
 
-            out.clear();
-            let mut state = self.state.lock().unwrap();
-            state.lines[msg.level] = msg.string;
+            // coalesce the messages that arrive until the next frame is due,
+            // so fast bars don't redraw all the lines on every update.
+            if let Some(last) = last_frame {
+                let deadline = last + self.state.lock().unwrap().frame_interval;
+                while self.running() {
+                    match self.chan.1.recv_deadline(deadline) {
+                        Ok(msg) => dirty |= self.apply(msg, &mut above),
+                        Err(_) => break,
+                    }
+                }
+            }
+            while let Ok(msg) = self.chan.1.try_recv() {
+                dirty |= self.apply(msg, &mut above);
+            }
 
             // and draw
-            if !first {
-                out += &move_cursor_up(state.nlines);
-            } else {
-                first = false;
+            if dirty {
+                self.draw(&above);
+                last_frame = Some(Instant::now());
             }
+        }
+        self.chan.0.set_listener(None);
+    }
+
+    // running returns whether `listen` should keep receiving messages.
+    fn running(&self) -> bool {
+        self.nbars.load(Ordering::SeqCst) > 0 || self.keep_alive.load(Ordering::SeqCst)
+    }
 
-            for l in state.lines.iter() {
-                out.push_str(&format!("\r{}\n", l));
+    // apply applies the message to the lines, and collects
+    // the lines to print above the bars in `above`.
+    // It returns whether the bars need to be drawn again.
+    fn apply(&self, msg: WriteMsg, above: &mut Vec<String>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match msg {
+            WriteMsg::Template { id, bar } => match state.position(id) {
+                Some(i) => state.lines[i].bar = Some(*bar),
+                // the bar was removed
+                None => return false,
+            },
+            WriteMsg::Update { id, progress } => {
+                let line = state.position(id).map(|i| &mut state.lines[i]);
+                match line.and_then(|l| l.bar.as_mut()) {
+                    Some(bar) => bar.set_progress(progress),
+                    None => return false,
+                }
+            }
+            WriteMsg::Text { id, string } => match state.position(id) {
+                Some(i) => state.lines[i].text = Some(string),
+                None => return false,
+            },
+            WriteMsg::Above(s) => above.push(s),
+            WriteMsg::Insert { id, parent } => state.insert_child(parent, id),
+            WriteMsg::Remove(id) => {
+                if let Some(i) = state.position(id) {
+                    state.lines.remove(i);
+                    state.nlines -= 1;
+                }
+            }
+            WriteMsg::Finish(id) | WriteMsg::Abandon(id) => {
+                self.nbars.fetch_sub(1, Ordering::SeqCst);
+                match state.position(id) {
+                    Some(i) => state.lines[i].finish(Instant::now()),
+                    None => return false,
+                }
+            }
+            WriteMsg::Suspend { cleared, resume } => {
+                drop(state);
+                // draw the pending changes, so the lines printed
+                // before the bar was suspended stay in order.
+                self.draw(above);
+                above.clear();
+                self.suspend(|| {
+                    let _ = cleared.send(());
+                    let _ = resume.recv();
+                });
+                return false;
             }
+            WriteMsg::Wake => return false,
+        }
+        true
+    }
 
-            printfl!(state.handle, "{}", out);
+    // draw prints the `above` lines and draws all the bars below them.
+    fn draw(&self, above: &[String]) {
+        let mut state = self.state.lock().unwrap();
+        let mut out = state.cursor_up();
+        for s in above.iter() {
+            // overwrite the lines of the previous frame,
+            // so pad the printed lines to the widest one.
+            let pad = state.max_width.saturating_sub(s.chars().count());
+            out.push_str(&format!("\r{}{}\n", s, " ".repeat(pad)));
         }
+        let height = state.drawn.saturating_sub(above.len());
+        out += &state.frame(height);
+        printfl!(state.handle, "{}", out);
+    }
+
+    // clear clears all the bars from the screen.
+    #[cfg(feature = "tokio")]
+    fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        let out = state.clear();
+        printfl!(state.handle, "{}", out);
+    }
+
+    /// suspend clears all the bars from the screen, calls `f`
+    /// and draws the bars again below whatever `f` printed.
+    /// `listen` does not draw while `f` is running,
+    /// so `f` has exclusive access to the terminal,
+    /// i.e. to prompt the user for input.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io::stdin;
+    /// use std::sync::Arc;
+    /// use std::thread;
+    /// use pb::MultiBar;
+    ///
+    /// let mb = Arc::new(MultiBar::new());
+    /// let mut p1 = mb.create_bar(10);
+    ///
+    /// let mb2 = mb.clone();
+    /// let _ = thread::spawn(move || {
+    ///     let mut answer = String::new();
+    ///     mb2.suspend(|| {
+    ///         println!("continue? [y/n]");
+    ///         stdin().read_line(&mut answer).unwrap();
+    ///     });
+    ///     if answer.trim() == "y" {
+    ///         p1.add(10);
+    ///     }
+    ///     p1.finish();
+    /// });
+    ///
+    /// mb.listen();
+    /// ```
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        let mut state = self.state.lock().unwrap();
+        if state.drawn == 0 {
+            return f();
+        }
+
+        let out = state.clear();
+        printfl!(state.handle, "{}", out);
+
+        let ret = suspended(f);
+
+        let out = state.frame(0);
+        printfl!(state.handle, "{}", out);
+        ret
     }
 
     /// create_bar creates new `ProgressBar` with `Pipe` as the writer.
@@ -167,7 +734,7 @@ impl<T: Write> MultiBar<T> {
     /// # Examples
     ///
     /// ```no_run
-    /// use pbr::MultiBar;
+    /// use pb::MultiBar;
     ///
     /// let mut mb = MultiBar::new();
     /// # let (count1, count2, count3) = (250, 62500, 15625000);
@@ -187,21 +754,99 @@ impl<T: Write> MultiBar<T> {
     /// mb.listen();
     /// ```
     pub fn create_bar(&self, total: u64) -> ProgressBar<Pipe> {
+        self.add_bar(total, |state| state.nlines)
+    }
+
+    /// insert_at creates new `ProgressBar` like `create_bar`,
+    /// but places it at the given `index` among all the lines
+    /// (bars and text lines), shifting the lines after it down.
+    /// If `index` is greater than the number of lines,
+    /// the bar is placed after the last line.
+    ///
+    /// Bars can be inserted at any time, also while `listen` is running.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pb::MultiBar;
+    ///
+    /// let mb = MultiBar::new();
+    /// let mut p2 = mb.create_bar(100);
+    /// // p1 is drawn above p2.
+    /// let mut p1 = mb.insert_at(0, 100);
+    /// // ...
+    /// ```
+    pub fn insert_at(&self, index: usize, total: u64) -> ProgressBar<Pipe> {
+        self.add_bar(total, |state| index.min(state.nlines))
+    }
+
+    /// insert_bar_before creates new `ProgressBar` like `create_bar`,
+    /// but places it right above `bar`.
+    /// If `bar` was removed, the new bar is placed after the last line.
+    pub fn insert_bar_before(&self, bar: &ProgressBar<Pipe>, total: u64) -> ProgressBar<Pipe> {
+        let id = bar.handle().id;
+        self.add_bar(total, |state| state.position(id).unwrap_or(state.nlines))
+    }
+
+    /// insert_bar_after creates new `ProgressBar` like `create_bar`,
+    /// but places it right below `bar`.
+    /// If `bar` was removed, the new bar is placed after the last line.
+    pub fn insert_bar_after(&self, bar: &ProgressBar<Pipe>, total: u64) -> ProgressBar<Pipe> {
+        let id = bar.handle().id;
+        self.add_bar(total, |state| state.position(id).map_or(state.nlines, |i| i + 1))
+    }
+
+    /// remove removes the line of `bar` from the MultiBar,
+    /// and the lines below it move up.
+    /// A bar that did not finish its work stops counting as
+    /// in progress, and is counted as abandoned, see `stats`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use pb::MultiBar;
+    ///
+    /// let mb = Arc::new(MultiBar::new());
+    /// mb.spawn();
+    ///
+    /// for _ in 0..100 {
+    ///     let mut p = mb.create_bar(10);
+    ///     p.add(10);
+    ///     p.finish();
+    ///     // don't keep the finished job on the screen.
+    ///     mb.remove(p);
+    /// }
+    ///
+    /// mb.join();
+    /// ```
+    pub fn remove(&self, bar: ProgressBar<Pipe>) {
+        self.chan.0.send(WriteMsg::Remove(bar.handle().id)).unwrap();
+        // dropping a bar that is not finished abandons it.
+        drop(bar);
+    }
+
+    // add_bar creates a new bar at the index returned by `index`.
+    fn add_bar<F>(&self, total: u64, index: F) -> ProgressBar<Pipe>
+    where
+        F: FnOnce(&State<T>) -> usize,
+    {
         let mut state = self.state.lock().unwrap();
-        state.lines.push(String::new());
-        state.nlines += 1;
+        let index = index(&state);
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        state.insert(index, id, None, 0);
 
         self.nbars.fetch_add(1, Ordering::SeqCst);
+        self.counters.created.fetch_add(1, Ordering::SeqCst);
 
-        let mut p = ProgressBar::on(
-            Pipe {
-                level: state.nlines - 1,
-                chan: self.chan.0.clone(),
-            },
-            total,
-        );
-
-        p.is_multibar = true;
+        let pipe = Pipe {
+            id,
+            chan: self.chan.0.clone(),
+            nbars: self.nbars.clone(),
+            next_id: self.next_id.clone(),
+            counters: self.counters.clone(),
+        };
+        let mut p = pipe.into_bar(total);
         p.add(0);
         p
     }
@@ -209,14 +854,20 @@ impl<T: Write> MultiBar<T> {
     /// println used to add text lines between the bars.
     /// i.e. you could add a header to your application,
     /// or text separators between bars.
+    /// To print log lines that scroll above the bars,
+    /// use `println_above` instead.
+    ///
+    /// The returned `TextLine` can change the text of the line
+    /// or remove it later on, i.e. to update a header with the
+    /// number of finished tasks.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use pbr::MultiBar;
+    /// use pb::MultiBar;
     ///
     /// let mut mb = MultiBar::new();
-    /// mb.println("Application header:");
+    /// let header = mb.println("Application header:");
     ///
     /// # let count = 250;
     /// let mut p1 = mb.create_bar(count);
@@ -230,19 +881,341 @@ impl<T: Write> MultiBar<T> {
     /// mb.println("Text line between bar2 and bar3");
     ///
     /// // ...
+    /// header.set_text("Application header (running):");
+    /// // ...
+    /// mb.listen();
+    /// ```
+    pub fn println(&self, s: &str) -> TextLine {
+        let mut state = self.state.lock().unwrap();
+        let index = state.nlines;
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        state.insert(index, id, Some(s.to_owned()), 0);
+        TextLine {
+            id,
+            chan: self.chan.0.clone(),
+        }
+    }
+
+    /// Set the maximum number of lines to draw, or `None` for the
+    /// height of the terminal, which is the default.
+    ///
+    /// If there are more lines than that, the text lines and only
+    /// the most recent unfinished bars are drawn, followed by
+    /// a summary line of the hidden bars, i.e. "… and 37 more (12 done)".
+    /// Child bars are drawn together with their parent bars.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pb::MultiBar;
+    ///
+    /// let mb = MultiBar::new();
+    /// mb.set_max_visible_lines(Some(10));
+    /// for _ in 0..100 {
+    ///     let mut p = mb.create_bar(100);
+    ///     // ...
+    /// }
+    /// // ...
+    /// mb.listen();
+    /// ```
+    pub fn set_max_visible_lines(&self, n: Option<usize>) {
+        self.state.lock().unwrap().max_lines = n;
+    }
+
+    /// Set the minimum time between two frames drawn by `listen`,
+    /// the default is 50 milliseconds.
+    ///
+    /// Updates of the bars that arrive within the interval are merged,
+    /// and only the latest state of every bar is drawn.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use pb::MultiBar;
+    ///
+    /// let mb = MultiBar::new();
+    /// // draw at most 10 frames per second.
+    /// mb.set_frame_interval(Duration::from_millis(100));
     /// // ...
     /// mb.listen();
     /// ```
-    pub fn println(&self, s: &str) {
+    pub fn set_frame_interval(&self, interval: Duration) {
+        self.state.lock().unwrap().frame_interval = interval;
+    }
+
+    /// Set the line of the terminal the MultiBar stays at,
+    /// the default is `Anchor::Top`.
+    ///
+    /// With `Anchor::Bottom`, the bars stay below the log lines
+    /// printed by `println_above`, like the output of `cargo build`.
+    /// When bars are removed, the last line stays in place and the
+    /// freed lines above the bars are left blank, then the next log
+    /// lines fill them before the bars move down again.
+    /// The bars are drawn below the existing output, so they are
+    /// only pinned to the bottom of the terminal once the output
+    /// and the log lines fill it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pb::{Anchor, MultiBar};
+    ///
+    /// let mb = MultiBar::new();
+    /// mb.set_anchor(Anchor::Bottom);
+    /// let mut p = mb.create_bar(10);
+    /// for i in 0..10 {
+    ///     mb.println_above(&format!("compiling crate {}", i));
+    ///     p.inc();
+    /// }
+    /// p.finish();
+    /// mb.listen();
+    /// ```
+    pub fn set_anchor(&self, anchor: Anchor) {
+        self.state.lock().unwrap().anchor = anchor;
+    }
+
+    /// stats returns the numbers of bars created, finished,
+    /// abandoned and still in progress so far.
+    /// A bar is abandoned if it is dropped, or removed by `remove`,
+    /// before it calls `finish`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pb::MultiBar;
+    ///
+    /// let mb = MultiBar::new();
+    /// let mut p1 = mb.create_bar(10);
+    /// let p2 = mb.create_bar(10);
+    /// p1.finish();
+    /// drop(p2);
+    ///
+    /// let stats = mb.stats();
+    /// assert_eq!((stats.finished, stats.abandoned), (1, 1));
+    /// ```
+    pub fn stats(&self) -> Stats {
+        self.counters.stats()
+    }
+
+    /// Draw the stats of the bars (see `stats`) in a status line
+    /// below all the bars, i.e. "2 in progress, 5 finished, 1 abandoned".
+    pub fn show_status_line(&self, show: bool) {
         let mut state = self.state.lock().unwrap();
-        state.lines.push(s.to_owned());
-        state.nlines += 1;
+        state.status = if show {
+            Some(self.counters.clone())
+        } else {
+            None
+        };
+    }
+
+    /// set_summary pins `bar` at the top or the bottom of the MultiBar
+    /// as a summary of all the bars: its current and total values
+    /// are the sums of those of all the bars, so its speed is the
+    /// combined throughput and its time left is the overall ETA.
+    ///
+    /// The summary bar is configured like any other `ProgressBar`,
+    /// i.e. its format, units and message.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io::sink;
+    /// use pb::{MultiBar, ProgressBar, SummaryPosition, Units};
+    ///
+    /// let mb = MultiBar::new();
+    /// let mut summary = ProgressBar::on(sink(), 0);
+    /// summary.set_units(Units::Bytes);
+    /// summary.message("Total ");
+    /// mb.set_summary(summary, SummaryPosition::Bottom);
+    ///
+    /// let mut p1 = mb.create_bar(1024);
+    /// let mut p2 = mb.create_bar(2048);
+    /// // ...
+    /// mb.listen();
+    /// ```
+    pub fn set_summary(&self, mut bar: ProgressBar<Sink>, position: SummaryPosition) {
+        bar.reset_start_time();
+        self.state.lock().unwrap().summary = Some(Summary { bar, position });
+    }
+
+    /// println_above prints the line `s` above all the bars
+    /// and redraws the bars below it.
+    /// Unlike `println`, it can be called while `listen` is running,
+    /// i.e. to log events from the threads that update the bars.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::thread;
+    /// use std::sync::Arc;
+    /// use pb::MultiBar;
+    ///
+    /// let mb = Arc::new(MultiBar::new());
+    /// let mut p1 = mb.create_bar(10);
+    ///
+    /// let mb2 = mb.clone();
+    /// let _ = thread::spawn(move || {
+    ///     for i in 0..10 {
+    ///         mb2.println_above(&format!("processing item {}", i));
+    ///         p1.inc();
+    ///     }
+    ///     p1.finish();
+    /// });
+    ///
+    /// mb.listen();
+    /// ```
+    pub fn println_above(&self, s: &str) {
+        self.chan.0.send(WriteMsg::Above(s.to_owned())).unwrap();
+    }
+
+    /// join waits until all the bars finish their work,
+    /// and stops the thread started by `spawn`.
+    /// Bars must not be created after calling join.
+    ///
+    /// See `spawn` for an example.
+    pub fn join(&self) {
+        self.keep_alive.store(false, Ordering::SeqCst);
+        self.chan.0.send(WriteMsg::Wake).unwrap();
+        if let Some(thread) = self.thread.lock().unwrap().take() {
+            thread.join().unwrap();
+        }
     }
 }
 
+impl<T: Write + Send + 'static> MultiBar<T> {
+    /// spawn starts to listen to all bars changes in its own thread,
+    /// so there is no need to call the blocking `listen`.
+    /// Unlike `listen`, the thread keeps running even if all
+    /// the bars are finished, so bars can be created at any time,
+    /// until `join` is called.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use std::thread;
+    /// use pb::MultiBar;
+    ///
+    /// let mb = Arc::new(MultiBar::new());
+    /// mb.spawn();
+    ///
+    /// for _ in 0..3 {
+    ///     let mut p = mb.create_bar(100);
+    ///     let _ = thread::spawn(move || {
+    ///         for _ in 0..100 {
+    ///             p.inc();
+    ///         }
+    ///         p.finish();
+    ///     });
+    /// }
+    ///
+    /// // wait for all the bars to finish.
+    /// mb.join();
+    /// ```
+    pub fn spawn(self: &Arc<Self>) {
+        self.keep_alive.store(true, Ordering::SeqCst);
+        let mb = self.clone();
+        let thread = thread::spawn(move || mb.listen());
+        *self.thread.lock().unwrap() = Some(thread);
+    }
+}
+
+#[cfg(test)]
+impl MultiBar<Vec<u8>> {
+    // output returns everything that was written to the handle.
+    pub(crate) fn output(&self) -> String {
+        let state = self.state.lock().unwrap();
+        String::from_utf8(state.handle.clone()).unwrap()
+    }
+
+    // step applies the pending messages and draws them in a single frame.
+    pub(crate) fn step(&self) {
+        let mut above = Vec::new();
+        let mut dirty = false;
+        while let Ok(msg) = self.chan.1.try_recv() {
+            dirty |= self.apply(msg, &mut above);
+        }
+        if dirty {
+            self.draw(&above);
+        }
+    }
+}
+
+/// TextLine is a text line of a `MultiBar`, created by `MultiBar::println`.
+/// Changes to the line are drawn by `listen` in the next frame.
+/// Dropping a TextLine keeps the line as is.
+#[derive(Clone)]
+pub struct TextLine {
+    id: usize,
+    chan: Chan,
+}
+
+impl TextLine {
+    /// Replace the text of the line with `s`.
+    pub fn set_text(&self, s: &str) {
+        let string = s.to_owned();
+        // the MultiBar may be gone already.
+        let _ = self.chan.send(WriteMsg::Text { id: self.id, string });
+    }
+
+    /// Remove the line from the MultiBar.
+    pub fn remove(self) {
+        let _ = self.chan.send(WriteMsg::Remove(self.id));
+    }
+}
+
+/// Pipe is the writer of the bars created by a `MultiBar`.
+/// The bars send their state to the MultiBar directly,
+/// so anything written to the Pipe is ignored.
 pub struct Pipe {
-    level: usize,
-    chan: Sender<WriteMsg>,
+    id: usize,
+    chan: Chan,
+    nbars: Arc<AtomicUsize>,
+    next_id: Arc<AtomicUsize>,
+    counters: Arc<Counters>,
+}
+
+impl Pipe {
+    // into_bar creates a new bar that is linked to the MultiBar of this pipe.
+    fn into_bar(self, total: u64) -> ProgressBar<Pipe> {
+        let link = Link {
+            id: self.id,
+            chan: self.chan.clone(),
+            counters: self.counters.clone(),
+            done: false,
+            style: None,
+            message: None,
+        };
+        let mut p = ProgressBar::on(self, total);
+        p.is_multibar = true;
+        p.link = Some(link);
+        p
+    }
+
+    // create_child creates a new bar in the MultiBar of this pipe,
+    // drawn below the bar of this pipe and its other children.
+    // The bar is not drawn until it is updated.
+    pub(crate) fn create_child(&self, total: u64) -> ProgressBar<Pipe> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.nbars.fetch_add(1, Ordering::SeqCst);
+        self.counters.created.fetch_add(1, Ordering::SeqCst);
+        self.chan
+            .send(WriteMsg::Insert {
+                id,
+                parent: self.id,
+            })
+            .unwrap();
+
+        let pipe = Pipe {
+            id,
+            chan: self.chan.clone(),
+            nbars: self.nbars.clone(),
+            next_id: self.next_id.clone(),
+            counters: self.counters.clone(),
+        };
+        pipe.into_bar(total)
+    }
 }
 
 impl Write for Pipe {
@@ -251,15 +1224,449 @@ impl Write for Pipe {
     }
 
     fn write(&mut self, buf: &[u8]) -> Result<usize> {
-        let s = from_utf8(buf).unwrap().to_owned();
-        self.chan
-            .send(WriteMsg {
-                // finish method emit empty string
-                done: s.is_empty(),
-                level: self.level,
-                string: s,
-            })
-            .unwrap();
         Ok(buf.len())
     }
-}
\ No newline at end of file
+}
+
+// Link connects a bar to its MultiBar, the bar sends its state through it.
+pub(crate) struct Link {
+    id: usize,
+    chan: Chan,
+    counters: Arc<Counters>,
+    done: bool,
+    // style is the style of the last template sent, if there is one.
+    style: Option<Style>,
+    // message is the last message sent.
+    message: Option<String>,
+}
+
+impl Link {
+    // style returns the style of the last template sent.
+    pub(crate) fn style(&self) -> Option<Style> {
+        self.style
+    }
+
+    // template sends the copy of the bar that the MultiBar renders,
+    // its following updates only send the progress of the bar.
+    pub(crate) fn template(&mut self, style: Style, bar: ProgressBar<Sink>) {
+        self.style = Some(style);
+        self.message = None;
+        let bar = Box::new(bar);
+        self.chan.send(WriteMsg::Template { id: self.id, bar }).unwrap();
+    }
+
+    // reset makes the next update send a new template,
+    // i.e. when the format of the bar changed.
+    pub(crate) fn reset(&mut self) {
+        self.style = None;
+    }
+
+    // update sends the progress of the bar, and its message
+    // if it changed since the last update.
+    pub(crate) fn update(&mut self, current: u64, total: u64, tick_state: usize, message: &str) {
+        let message = match &self.message {
+            Some(m) if m == message => None,
+            _ => {
+                self.message = Some(message.to_owned());
+                Some(message.to_owned())
+            }
+        };
+        let progress = Progress {
+            current,
+            total,
+            tick_state,
+            message,
+        };
+        self.chan.send(WriteMsg::Update { id: self.id, progress }).unwrap();
+    }
+
+    // text replaces the bar with the text `s`.
+    pub(crate) fn text(&self, s: &str) {
+        let string = s.to_owned();
+        self.chan.send(WriteMsg::Text { id: self.id, string }).unwrap();
+    }
+
+    // above prints the line `s` above all the bars.
+    pub(crate) fn above(&self, s: &str) {
+        self.chan.send(WriteMsg::Above(s.to_owned())).unwrap();
+    }
+
+    // suspend asks `listen` to clear the bars, and calls `f`
+    // while `listen` waits for it to return.
+    // Without `listen` running on another thread, `f` is called as is.
+    pub(crate) fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        if !self.chan.listening() {
+            return f();
+        }
+        let (cleared_tx, cleared) = bounded(1);
+        let (resume, resume_rx) = bounded::<()>(0);
+        let msg = WriteMsg::Suspend {
+            cleared: cleared_tx,
+            resume: resume_rx,
+        };
+        // the message is dropped without an answer if the MultiBar is gone,
+        // or left unanswered if `listen` returns before it receives it.
+        if self.chan.send(msg).is_ok() {
+            while let Err(RecvTimeoutError::Timeout) = cleared.recv_timeout(FRAME_INTERVAL) {
+                if !self.chan.listening() {
+                    break;
+                }
+            }
+        }
+        let ret = f();
+        drop(resume);
+        // wake up `listen_async`, that checks whether `f` returned.
+        let _ = self.chan.send(WriteMsg::Wake);
+        ret
+    }
+
+    // finish notifies that the bar finished its work.
+    pub(crate) fn finish(&mut self) {
+        if !self.done {
+            self.done = true;
+            self.counters.finished.fetch_add(1, Ordering::SeqCst);
+            self.chan.send(WriteMsg::Finish(self.id)).unwrap();
+        }
+    }
+}
+
+impl Drop for Link {
+    fn drop(&mut self) {
+        // a bar that is dropped before it finished is abandoned,
+        // so `listen` doesn't wait for it forever.
+        if !self.done {
+            self.counters.abandoned.fetch_add(1, Ordering::SeqCst);
+            let _ = self.chan.send(WriteMsg::Abandon(self.id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::multi::{Anchor, MultiBar, Stats, SummaryPosition, WriteMsg};
+    use crate::ProgressBar;
+    use crate::tty::move_cursor_up;
+    use std::sync::Arc;
+
+    #[test]
+    fn println_above() {
+        let mb = MultiBar::on(Vec::new());
+        mb.println("header");
+        let mut p = mb.create_bar(1);
+        mb.step();
+        mb.println_above("log line");
+        p.finish();
+        mb.listen();
+
+        let out = mb.output();
+        let up = move_cursor_up(2);
+        let mut frames = out.split(&up);
+        assert!(frames.next().unwrap().starts_with("\rheader\n\r0 / 1 "));
+        // the messages received together are drawn in a single frame,
+        // and the bar is drawn on a new line below the previous frame.
+        let second = frames.next().unwrap();
+        assert!(second.starts_with("\rlog line ") && second.contains("\n\r1 / 1 "));
+        assert_eq!(frames.next(), None);
+    }
+
+    #[test]
+    fn text_line() {
+        let mb = MultiBar::on(Vec::new());
+        let header = mb.println("header");
+        let footer = mb.println("footer");
+        let mut p = mb.create_bar(1);
+        mb.step();
+        header.set_text("new header");
+        footer.remove();
+        p.finish();
+        mb.listen();
+
+        let state = mb.state.lock().unwrap();
+        assert_eq!(state.screen.len(), 2);
+        assert_eq!(state.screen[0], "new header");
+        assert!(state.screen[1].starts_with("1 / 1 "));
+    }
+
+    #[test]
+    fn spawn() {
+        let mb = Arc::new(MultiBar::on(Vec::new()));
+        mb.spawn();
+        mb.create_bar(1).finish();
+        // the thread keeps running after all the bars are finished.
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        mb.create_bar(1).finish();
+        mb.join();
+
+        let state = mb.state.lock().unwrap();
+        assert_eq!(state.screen.len(), 2);
+        assert!(state.screen.iter().all(|l| l.starts_with("1 / 1 ")));
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mb = MultiBar::on(Vec::new());
+        mb.println("header");
+        let mut p1 = mb.create_bar(1);
+        let mut p4 = mb.create_bar(1);
+        let mut p3 = mb.insert_bar_before(&p4, 1);
+        let p2 = mb.insert_bar_after(&p1, 1);
+        let mut p0 = mb.insert_at(1, 1);
+        let ids = |mb: &MultiBar<Vec<u8>>| -> Vec<usize> {
+            let state = mb.state.lock().unwrap();
+            state.lines.iter().map(|l| l.id).collect()
+        };
+        assert_eq!(ids(&mb), vec![0, 5, 1, 4, 3, 2]);
+
+        mb.step();
+        mb.remove(p2);
+        for p in [&mut p0, &mut p1, &mut p3, &mut p4] {
+            p.finish();
+        }
+        mb.listen();
+        assert_eq!(ids(&mb), vec![0, 5, 1, 3, 2]);
+
+        // the left over line of the removed bar is cleared.
+        let out = mb.output();
+        let (up6, up5) = (move_cursor_up(6), move_cursor_up(5));
+        let frame = out.rsplit(&up6).next().unwrap().split(&up5).next().unwrap();
+        let mut lines = frame.lines().skip(5);
+        assert!(lines.next().unwrap().trim().is_empty());
+        assert_eq!(lines.next(), Some(move_cursor_up(1).as_str()));
+    }
+
+    #[test]
+    fn redraw_changed() {
+        let mb = MultiBar::on(Vec::new());
+        mb.println("header");
+        let mut p = mb.create_bar(10);
+        p.show_speed = false;
+        p.show_time_left = false;
+        p.message("copy ");
+        p.add(1);
+        mb.step();
+        p.add(1);
+        mb.step();
+
+        // only the end of the bar line is redrawn.
+        let out = mb.output();
+        let last = out.rsplit(&move_cursor_up(2)).next().unwrap();
+        assert!(last.starts_with("\n\r\x1B[5C2 / 10 "));
+        assert!(!last.contains("header"));
+    }
+
+    #[test]
+    fn anchor_bottom() {
+        let mb = MultiBar::on(Vec::new());
+        mb.set_anchor(Anchor::Bottom);
+        let p1 = mb.create_bar(1);
+        let mut p2 = mb.create_bar(1);
+        mb.step();
+        mb.remove(p1);
+        p2.finish();
+        mb.listen();
+
+        // the line of the removed bar is blank, and the bar below it stays in place.
+        let out = mb.output();
+        let last = out.rsplit(&move_cursor_up(2)).next().unwrap();
+        let mut lines = last.lines();
+        assert!(lines.next().unwrap().trim().is_empty());
+        assert!(lines.next().unwrap().starts_with("\r1 / 1 "));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn anchor_bottom_log() {
+        let mb = MultiBar::on(Vec::new());
+        mb.set_anchor(Anchor::Bottom);
+        let p1 = mb.create_bar(1);
+        let mut p2 = mb.create_bar(1);
+        mb.step();
+        mb.remove(p1);
+        mb.step();
+        mb.println_above("log line");
+        p2.finish();
+        mb.listen();
+
+        // the log line fills the blank line, instead of a new line.
+        let out = mb.output();
+        let last = out.rsplit(&move_cursor_up(2)).next().unwrap();
+        let mut lines = last.lines();
+        assert!(lines.next().unwrap().starts_with("\rlog line "));
+        assert!(lines.next().unwrap().starts_with("\r1 / 1 "));
+        assert_eq!(lines.next(), None);
+        assert_eq!(mb.state.lock().unwrap().drawn, 1);
+    }
+
+    #[test]
+    fn stats() {
+        let mb = MultiBar::on(Vec::new());
+        mb.show_status_line(true);
+        let mut p1 = mb.create_bar(1);
+        let p2 = mb.create_bar(1);
+        let _p3 = mb.create_bar(1);
+        p1.finish();
+        mb.remove(p2);
+        assert_eq!(
+            mb.stats(),
+            Stats {
+                created: 3,
+                finished: 1,
+                abandoned: 1,
+                in_progress: 1,
+            }
+        );
+
+        mb.step();
+        let state = mb.state.lock().unwrap();
+        assert_eq!(state.screen.len(), 3);
+        assert_eq!(state.screen[2], "1 in progress, 1 finished, 1 abandoned");
+    }
+
+    #[test]
+    fn max_visible_lines() {
+        let mb = MultiBar::on(Vec::new());
+        mb.set_max_visible_lines(Some(3));
+        mb.println("header");
+        let _bars: Vec<_> = (0..5).map(|_| mb.create_bar(1)).collect();
+
+        let mut state = mb.state.lock().unwrap();
+        state.lines[5].done = true;
+        let (shown, summary) = state.visible();
+        // the header and the most recent unfinished bar are shown.
+        assert_eq!(shown, vec![0, 4]);
+        assert_eq!(summary.as_deref(), Some("… and 4 more (1 done)"));
+        assert!(state.frame(0).ends_with("\r… and 4 more (1 done)\n"));
+        assert_eq!(state.drawn, 3);
+    }
+
+    #[test]
+    fn max_visible_lines_child() {
+        let mb = MultiBar::on(Vec::new());
+        mb.set_max_visible_lines(Some(3));
+        let parent = mb.create_parent_bar(10);
+        let _child = parent.create_child(10);
+        let _bars: Vec<_> = (0..2).map(|_| mb.create_bar(1)).collect();
+        mb.step();
+
+        let mut state = mb.state.lock().unwrap();
+        assert_eq!(state.lines[1].depth, 1);
+        state.lines[2].done = true;
+        state.lines[3].done = true;
+        // the child bar is shown below its parent bar.
+        let (shown, summary) = state.visible();
+        assert_eq!(shown, vec![0, 1]);
+        assert_eq!(summary.as_deref(), Some("… and 2 more (2 done)"));
+    }
+
+    #[test]
+    fn summary() {
+        let mb = MultiBar::on(Vec::new());
+        let mut summary = ProgressBar::on(std::io::sink(), 0);
+        summary.show_speed = false;
+        summary.show_time_left = false;
+        summary.message("Total ");
+        mb.set_summary(summary, SummaryPosition::Top);
+
+        let mut p1 = mb.create_bar(10);
+        p1.add(5);
+        let parent = mb.create_parent_bar(100);
+        let mut p2 = parent.create_child(30);
+        p2.add(15);
+        mb.step();
+        p1.finish();
+        p2.finish();
+        mb.listen();
+
+        // the parent bar is not counted.
+        let out = mb.output();
+        assert!(out.contains("\rTotal 20 / 40 "));
+        let state = mb.state.lock().unwrap();
+        assert!(state.screen[0].starts_with("Total 40 / 40 "));
+    }
+
+    #[test]
+    fn suspend() {
+        let mb = MultiBar::on(Vec::new());
+        mb.println("header");
+        mb.create_bar(1).finish();
+        mb.listen();
+
+        let before = mb.output();
+        assert_eq!(mb.suspend(|| 42), 42);
+        let out = mb.output();
+        let up = move_cursor_up(2);
+        let (blank, frame) = {
+            let mut state = mb.state.lock().unwrap();
+            (format!("\r{}\n", " ".repeat(state.max_width)), state.frame(0))
+        };
+        assert_eq!(
+            &out[before.len()..],
+            format!("{}{}{}{}", up, blank.repeat(2), up, frame),
+        );
+    }
+
+    #[test]
+    fn suspend_bar_without_listen() {
+        let mb = MultiBar::on(Vec::new());
+        let mut p = mb.create_bar(1);
+        // nothing is listening yet, or anymore.
+        assert_eq!(p.suspend(|| 1), 1);
+        p.finish();
+        mb.listen();
+        assert_eq!(p.suspend(|| 2), 2);
+    }
+
+    #[test]
+    fn suspend_bar() {
+        let mb = Arc::new(MultiBar::on(Vec::new()));
+        mb.println("header");
+        let mut p = mb.create_bar(2);
+        mb.spawn();
+        p.inc();
+        while !mb.chan.0.listening() {
+            std::thread::yield_now();
+        }
+        // listen holds the lock of the bars while `f` is running.
+        assert!(p.suspend(|| mb.state.try_lock().is_err()));
+        p.finish();
+        mb.join();
+
+        let out = mb.output();
+        let up = move_cursor_up(2);
+        let blank = format!("\r{}\n", " ".repeat(mb.state.lock().unwrap().max_width));
+        let cleared = format!("{}{}{}", up, blank.repeat(2), up);
+        let (before, after) = out.split_once(&cleared).unwrap();
+        assert!(before.contains("\rheader\n\r1 / 2 "));
+        // the bars are drawn again below the cleared lines.
+        assert!(after.starts_with("\rheader\n\r1 / 2 "));
+    }
+
+    #[test]
+    fn typed_updates() {
+        let mb = MultiBar::on(Vec::new());
+        let mut p = mb.create_bar(10);
+        while mb.chan.1.try_recv().is_ok() {}
+        p.inc();
+        let msg = mb.chan.1.try_recv().unwrap();
+        assert!(matches!(msg, WriteMsg::Update { progress, .. }
+            if progress.current == 1 && progress.message.is_none()));
+
+        // the template is sent again when the style changes.
+        p.show_speed = false;
+        p.message("copy ");
+        p.inc();
+        assert!(matches!(mb.chan.1.try_recv(), Ok(WriteMsg::Template { .. })));
+        let msg = mb.chan.1.try_recv().unwrap();
+        assert!(matches!(msg, WriteMsg::Update { progress, .. }
+            if progress.message.as_deref() == Some("copy ")));
+    }
+
+    #[test]
+    fn text_line_outlives_multibar() {
+        let mb = MultiBar::on(Vec::new());
+        let header = mb.println("header");
+        drop(mb);
+        header.set_text("new header");
+        header.remove();
+    }
+}
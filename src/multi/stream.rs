@@ -0,0 +1,319 @@
+use std::collections::VecDeque;
+use std::future::{poll_fn, Future};
+use std::io::Write;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::thread;
+use std::time::Instant;
+use atomic_waker::AtomicWaker;
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use futures_core::Stream;
+use tokio::time::Sleep;
+use super::{MultiBar, Stats, WriteMsg};
+
+// Messages is the stream of the messages sent to a MultiBar.
+// The senders wake it up through the waker of their `Chan`.
+struct Messages<'a> {
+    receiver: &'a Receiver<WriteMsg>,
+    waker: &'a AtomicWaker,
+}
+
+impl Stream for Messages<'_> {
+    type Item = WriteMsg;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<WriteMsg>> {
+        // register before receiving, so a message
+        // sent in between wakes up the task again.
+        self.waker.register(cx.waker());
+        match self.receiver.try_recv() {
+            Ok(msg) => Poll::Ready(Some(msg)),
+            Err(TryRecvError::Empty) => Poll::Pending,
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+        }
+    }
+}
+
+/// Frames is the stream of the frames drawn by a `MultiBar`,
+/// created by `MultiBar::frames`.
+/// Every item is the stats of the bars when the frame was drawn.
+pub struct Frames<'a, T: Write> {
+    mb: &'a MultiBar<T>,
+    messages: Messages<'a>,
+    above: Vec<String>,
+    dirty: bool,
+    last_frame: Option<Instant>,
+    // sleep waits until the next frame is due.
+    sleep: Option<Pin<Box<Sleep>>>,
+    // suspended is disconnected once the bar that suspended
+    // the bars returned from `suspend`.
+    suspended: Option<Receiver<()>>,
+    // pending is the suspend requests of other bars, that wait
+    // for the bars to be resumed.
+    pending: VecDeque<(Sender<()>, Receiver<()>)>,
+}
+
+impl<T: Write> Frames<'_, T> {
+    // handle applies a message. Unlike `listen`, suspend requests
+    // don't block the task until `suspend` returns: the bars are
+    // cleared, and no frame is drawn until `resumed` is true.
+    fn handle(&mut self, msg: WriteMsg) {
+        match msg {
+            WriteMsg::Suspend { cleared, resume } if self.suspended.is_some() => {
+                self.pending.push_back((cleared, resume));
+            }
+            WriteMsg::Suspend { cleared, resume } => {
+                // draw the pending changes, so the lines printed
+                // before the bar was suspended stay in order.
+                self.mb.draw(&self.above);
+                self.above.clear();
+                self.mb.clear();
+                let _ = cleared.send(());
+                self.suspended = Some(resume);
+            }
+            msg => self.dirty |= self.mb.apply(msg, &mut self.above),
+        }
+    }
+
+    // resumed returns whether the bars are not suspended,
+    // and lets the next pending request suspend them.
+    fn resumed(&mut self) -> bool {
+        while let Some(resume) = &self.suspended {
+            if let Err(TryRecvError::Empty) = resume.try_recv() {
+                return false;
+            }
+            self.suspended = None;
+            self.dirty = true;
+            if let Some((cleared, resume)) = self.pending.pop_front() {
+                let _ = cleared.send(());
+                self.suspended = Some(resume);
+            }
+        }
+        true
+    }
+
+    // draw applies the pending messages, and draws them
+    // in a single frame if the bars need to be drawn again.
+    fn draw(&mut self) -> Option<Stats> {
+        while let Ok(msg) = self.mb.chan.1.try_recv() {
+            self.handle(msg);
+        }
+        if !self.resumed() || !self.dirty {
+            return None;
+        }
+        self.mb.draw(&self.above);
+        self.above.clear();
+        self.dirty = false;
+        self.last_frame = Some(Instant::now());
+        Some(self.mb.stats())
+    }
+}
+
+impl<T: Write> Stream for Frames<'_, T> {
+    type Item = Stats;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Stats>> {
+        let this = self.get_mut();
+        // the task may move to another thread between polls.
+        this.mb.chan.0.set_listener(Some(thread::current().id()));
+        loop {
+            if let Some(sleep) = &mut this.sleep {
+                ready!(sleep.as_mut().poll(cx));
+                this.sleep = None;
+                if let Some(stats) = this.draw() {
+                    return Poll::Ready(Some(stats));
+                }
+            }
+            if !this.mb.running() {
+                this.mb.chan.0.set_listener(None);
+                return Poll::Ready(None);
+            }
+            let msg = match ready!(Pin::new(&mut this.messages).poll_next(cx)) {
+                Some(msg) => msg,
+                None => {
+                    this.mb.chan.0.set_listener(None);
+                    return Poll::Ready(None);
+                }
+            };
+            this.handle(msg);
+
+            // coalesce the messages that arrive until the next frame is due.
+            match this.last_frame {
+                Some(last) if this.mb.running() => {
+                    let deadline = last + this.mb.state.lock().unwrap().frame_interval;
+                    this.sleep = Some(Box::pin(tokio::time::sleep_until(deadline.into())));
+                }
+                _ => {
+                    if let Some(stats) = this.draw() {
+                        return Poll::Ready(Some(stats));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T: Write> Drop for Frames<'_, T> {
+    fn drop(&mut self) {
+        self.mb.chan.0.set_listener(None);
+    }
+}
+
+impl<T: Write> MultiBar<T> {
+    /// frames returns the stream of the frames drawn by the MultiBar,
+    /// that ends when all bars finish. Every frame is drawn when
+    /// the stream is polled, so the stream must be polled until
+    /// it ends, like `listen_async` does, i.e. in a `select!` with
+    /// other futures of the task.
+    ///
+    /// The items are the stats of the bars when a frame was drawn.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::future::poll_fn;
+    /// use std::pin::Pin;
+    /// use futures_core::Stream;
+    /// use pb::MultiBar;
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() {
+    ///     let mb = MultiBar::new();
+    ///     let mut p = mb.create_bar(100);
+    ///     tokio::task::spawn_blocking(move || {
+    ///         for _ in 0..100 {
+    ///             p.inc();
+    ///         }
+    ///         p.finish();
+    ///     });
+    ///     let mut frames = mb.frames();
+    ///     while let Some(stats) = poll_fn(|cx| Pin::new(&mut frames).poll_next(cx)).await {
+    ///         eprintln!("{}", stats);
+    ///     }
+    /// }
+    /// ```
+    pub fn frames(&self) -> Frames<'_, T> {
+        Frames {
+            mb: self,
+            messages: Messages {
+                receiver: &self.chan.1,
+                waker: &self.chan.0.waker,
+            },
+            above: Vec::new(),
+            dirty: false,
+            last_frame: None,
+            sleep: None,
+            suspended: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// listen_async is like `listen`, but it waits for the bars
+    /// changes without blocking the thread, so it can run as a task
+    /// of a tokio runtime. The returned future completes when all
+    /// bars finish.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pb::MultiBar;
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() {
+    ///     let mb = MultiBar::new();
+    ///     let mut p = mb.create_bar(100);
+    ///     tokio::task::spawn_blocking(move || {
+    ///         for _ in 0..100 {
+    ///             p.inc();
+    ///         }
+    ///         p.finish();
+    ///     });
+    ///     mb.listen_async().await;
+    /// }
+    /// ```
+    pub async fn listen_async(&self) {
+        let mut frames = self.frames();
+        while poll_fn(|cx| Pin::new(&mut frames).poll_next(cx)).await.is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::MultiBar;
+    use futures_core::Stream;
+    use std::future::poll_fn;
+    use std::pin::Pin;
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::Duration;
+    use tokio::runtime::Handle;
+
+    #[tokio::test]
+    async fn listen_async() {
+        let mb = MultiBar::on(Vec::new());
+        let mut p = mb.create_bar(2);
+        let thread = thread::spawn(move || {
+            p.inc();
+            thread::sleep(Duration::from_millis(10));
+            p.inc();
+            p.finish();
+        });
+        mb.listen_async().await;
+        thread.join().unwrap();
+
+        let state = mb.state.lock().unwrap();
+        assert!(state.screen[0].starts_with("2 / 2 "));
+    }
+
+    #[tokio::test]
+    async fn frames() {
+        let mb = MultiBar::on(Vec::new());
+        let mut p = mb.create_bar(2);
+        let thread = thread::spawn(move || {
+            p.inc();
+            thread::sleep(Duration::from_millis(10));
+            p.inc();
+            p.finish();
+        });
+        let mut frames = mb.frames();
+        let mut last = None;
+        while let Some(stats) = poll_fn(|cx| Pin::new(&mut frames).poll_next(cx)).await {
+            last = Some(stats);
+        }
+        thread.join().unwrap();
+        assert_eq!(last.map(|s| s.finished), Some(1));
+    }
+
+    #[tokio::test]
+    async fn suspend_bar() {
+        let mb = Arc::new(MultiBar::on(Vec::new()));
+        let mut p = mb.create_bar(2);
+        let listener = tokio::spawn({
+            let mb = mb.clone();
+            async move { mb.listen_async().await }
+        });
+        // the listener runs on this thread, so `listening` is false here.
+        while mb.chan.0.listener.lock().unwrap().is_none() {
+            tokio::task::yield_now().await;
+        }
+
+        // the listener runs on the same runtime as the task that
+        // suspends the bar, so it must not block while f is running.
+        let handle = Handle::current();
+        let (sender, receiver) = mpsc::channel();
+        let p = tokio::task::spawn_blocking(move || {
+            p.inc();
+            let ok = p.suspend(|| {
+                handle.spawn(async move { sender.send(()).unwrap() });
+                receiver.recv_timeout(Duration::from_secs(5)).is_ok()
+            });
+            p.inc();
+            p.finish();
+            ok
+        });
+        assert!(p.await.unwrap());
+        listener.await.unwrap();
+
+        let state = mb.state.lock().unwrap();
+        assert!(state.screen[0].starts_with("2 / 2 "));
+    }
+}
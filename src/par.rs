@@ -0,0 +1,184 @@
+//! Parallel iterators of rayon that advance a shared `ProgressBar`.
+use std::io::{Stdout, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use rayon::iter::plumbing::{Consumer, ProducerCallback, UnindexedConsumer};
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use crate::ProgressBar;
+
+/// PbParIter is a parallel iterator that increments its bar for every
+/// item, from any of the worker threads, and finishes the bar once
+/// all the items are done. See `ParallelProgressIterator`.
+///
+/// The worker threads don't wait for each other to draw the bar:
+/// the items are counted, and drawn by the thread that gets the bar,
+/// while the other threads carry on with their items.
+pub struct PbParIter<I, T: Write> {
+    iter: I,
+    progress_bar: Arc<Mutex<ProgressBar<T>>>,
+    // count is the number of items that are not added to the bar yet.
+    count: Arc<AtomicU64>,
+}
+
+impl<I, T: Write> PbParIter<I, T> {
+    /// Get the progress bar that is shared by the worker threads,
+    /// i.e. to set its message while iterating.
+    pub fn progress_bar(&self) -> Arc<Mutex<ProgressBar<T>>> {
+        self.progress_bar.clone()
+    }
+
+    // counter returns the function that counts an item, and draws
+    // the counted items unless another thread is drawing the bar.
+    fn counter<Item>(&self) -> impl Fn(&Item) + Send + Sync
+    where
+        T: Send,
+    {
+        let (pb, count) = (self.progress_bar.clone(), self.count.clone());
+        move |_| {
+            count.fetch_add(1, Ordering::Relaxed);
+            if let Ok(mut pb) = pb.try_lock() {
+                pb.add(count.swap(0, Ordering::Relaxed));
+            }
+        }
+    }
+}
+
+// finish adds the items that are not drawn yet to the bar, and finishes it.
+fn finish<T: Write>(pb: &Mutex<ProgressBar<T>>, count: &AtomicU64) {
+    let mut pb = pb.lock().unwrap();
+    pb.add(count.swap(0, Ordering::Relaxed));
+    pb.finish();
+}
+
+impl<I, T> ParallelIterator for PbParIter<I, T>
+where
+    I: ParallelIterator,
+    T: Write + Send,
+{
+    type Item = I::Item;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let counter = self.counter();
+        let res = self.iter.inspect(counter).drive_unindexed(consumer);
+        finish(&self.progress_bar, &self.count);
+        res
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        self.iter.opt_len()
+    }
+}
+
+impl<I, T> IndexedParallelIterator for PbParIter<I, T>
+where
+    I: IndexedParallelIterator,
+    T: Write + Send,
+{
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        let counter = self.counter();
+        let res = self.iter.inspect(counter).drive(consumer);
+        finish(&self.progress_bar, &self.count);
+        res
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        let counter = self.counter();
+        let res = self.iter.inspect(counter).with_producer(callback);
+        finish(&self.progress_bar, &self.count);
+        res
+    }
+}
+
+/// ParallelProgressIterator attaches a `ProgressBar` to any parallel
+/// iterator of rayon, like `ProgressIterator` does for iterators.
+/// The bar is shared by all the worker threads.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rayon::prelude::*;
+/// use pb::ParallelProgressIterator;
+///
+/// let sum: u64 = (0..1000u64).into_par_iter().progress().map(|n| n * n).sum();
+/// ```
+pub trait ParallelProgressIterator: ParallelIterator {
+    /// Draw a bar on stdout, its total is the length of the
+    /// iterator if it is known, otherwise it is drawn as a spinner.
+    fn progress(self) -> PbParIter<Self, Stdout> {
+        let total = self.opt_len().unwrap_or(0);
+        self.progress_with(ProgressBar::new(total as u64))
+    }
+
+    /// Draw a bar on stdout with the total `n`.
+    fn progress_count(self, n: u64) -> PbParIter<Self, Stdout> {
+        self.progress_with(ProgressBar::new(n))
+    }
+
+    /// Draw the bar `pb`, including a bar of a `MultiBar`.
+    fn progress_with<T: Write + Send>(self, pb: ProgressBar<T>) -> PbParIter<Self, T> {
+        PbParIter {
+            iter: self,
+            progress_bar: Arc::new(Mutex::new(pb)),
+            count: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+impl<I: ParallelIterator> ParallelProgressIterator for I {}
+
+#[cfg(test)]
+mod test {
+    use crate::par::ParallelProgressIterator;
+    use crate::ProgressBar;
+    use rayon::prelude::*;
+
+    #[test]
+    fn unindexed() {
+        let it = (0..100u64)
+            .into_par_iter()
+            .filter(|n| n % 2 == 0)
+            .progress_with(ProgressBar::on(Vec::new(), 50));
+        let pb = it.progress_bar();
+        assert_eq!(it.count(), 50);
+
+        let pb = pb.lock().unwrap();
+        assert_eq!(pb.current, 50);
+        assert!(pb.is_finish);
+    }
+
+    #[test]
+    fn indexed() {
+        let it = (0..100u64)
+            .into_par_iter()
+            .progress_with(ProgressBar::on(Vec::new(), 100));
+        let pb = it.progress_bar();
+        let squares: Vec<_> = it.map(|n| n * n).collect();
+        assert_eq!(squares[9], 81);
+
+        let pb = pb.lock().unwrap();
+        assert_eq!(pb.current, 100);
+        assert!(pb.is_finish);
+    }
+
+    #[test]
+    fn spinner() {
+        let it = (0..100u64)
+            .into_par_iter()
+            .filter(|n| n % 2 == 0)
+            .progress_with(ProgressBar::on(Vec::new(), 0));
+        let pb = it.progress_bar();
+        assert_eq!(it.count(), 50);
+
+        // all the counted items are added to the bar.
+        let pb = pb.lock().unwrap();
+        assert_eq!(pb.current, 50);
+        assert!(pb.is_finish);
+    }
+}
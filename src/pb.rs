@@ -1,6 +1,12 @@
-use std::io::{self, Stdout, Write};
+use std::sync::Arc;
+use crate::tree::Node;
+use crate::multi::Link;
+use std::cell::Cell;
+use std::io::{self, Sink, Stdout, Write};
 use std::time::{Duration, Instant};
-use crate::tty::{terminal_size, Width};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::tty::{redraw_line, terminal_size, Width};
 
 macro_rules! kb_fmt {
     ($n: ident) => {{
@@ -20,17 +26,93 @@ const TICK_FORMAT: &str = "\\|/-";
 
 // Output type format,
 // indicate which format wil be used in the speed box.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Units {
     Default,
     Bytes,
 }
 
+/// Snapshot is the state of a progress bar, that is saved
+/// by `ProgressBar::snapshot` and restored by `ProgressBar::restore`,
+/// i.e. to continue a long job after a restart.
+/// With the `serde` feature, it can be serialized.
+///
+/// A snapshot has no rate history: the speed of a bar is the average
+/// since its start, so the current value and the elapsed time are
+/// enough to restore the same speed and time left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snapshot {
+    pub current: u64,
+    pub total: u64,
+    pub elapsed: Duration,
+    pub message: String,
+    pub units: Units,
+}
+
+// Style is the configuration of a bar that rarely changes.
+// The MultiBar of the bar keeps a copy of the bar to render it,
+// which is only sent again when the style changes.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct Style {
+    start_time: Instant,
+    paused_at: Option<Instant>,
+    units: Units,
+    width: Option<usize>,
+    is_finish: bool,
+    show_bar: bool,
+    show_speed: bool,
+    show_percent: bool,
+    show_counter: bool,
+    show_time_left: bool,
+    show_tick: bool,
+    show_message: bool,
+}
+
+// Progress is the state of a bar that changes on every update,
+// that is sent to the MultiBar of the bar.
+pub(crate) struct Progress {
+    pub(crate) current: u64,
+    pub(crate) total: u64,
+    pub(crate) tick_state: usize,
+    // message is `None` if it did not change.
+    pub(crate) message: Option<String>,
+}
+
+thread_local! {
+    // SUSPENDED is set while the bars are suspended by this thread,
+    // so the records logged by `f` don't try to suspend them again.
+    static SUSPENDED: Cell<bool> = const { Cell::new(false) };
+}
+
+// Suspended resets SUSPENDED once `f` returns, or panics.
+struct Suspended(bool);
+
+impl Drop for Suspended {
+    fn drop(&mut self) {
+        SUSPENDED.with(|s| s.set(self.0));
+    }
+}
+
+// suspended calls `f` with SUSPENDED set.
+pub(crate) fn suspended<F: FnOnce() -> R, R>(f: F) -> R {
+    let _guard = Suspended(SUSPENDED.with(|s| s.replace(true)));
+    f()
+}
+
+// is_suspended returns whether the bars are suspended by this thread.
+#[cfg_attr(not(feature = "log"), allow(dead_code))]
+pub(crate) fn is_suspended() -> bool {
+    SUSPENDED.with(|s| s.get())
+}
+
 pub struct ProgressBar<T: Write> {
     start_time: Instant,
+    paused_at: Option<Instant>,
     units: Units,
     pub total: u64,
-    current: u64,
+    pub(crate) current: u64,
     bar_start: String,
     bar_current: String,
     bar_current_n: String,
@@ -42,6 +124,8 @@ pub struct ProgressBar<T: Write> {
     message: String,
     last_refresh_time: Instant,
     max_refresh_rate: Option<Duration>,
+    // last_line is the line on the screen, so only what changed is redrawn.
+    last_line: String,
     pub is_finish: bool,
     pub is_multibar: bool,
     pub show_bar: bool,
@@ -51,6 +135,11 @@ pub struct ProgressBar<T: Write> {
     pub show_time_left: bool,
     pub show_tick: bool,
     pub show_message: bool,
+    // parent is the node of the parent bar and the index
+    // of this bar among its children, if there is one.
+    pub(crate) parent: Option<(Arc<Node>, usize)>,
+    // link connects the bar to its MultiBar, if there is one.
+    pub(crate) link: Option<Link>,
     handle: T,
 }
 
@@ -61,7 +150,7 @@ impl ProgressBar<Stdout> {
     ///
     /// ```no_run
     /// use std::thread;
-    /// use pbr::{ProgressBar, Units};
+    /// use pb::{ProgressBar, Units};
     ///
     /// let count = 1000;
     /// let mut pb = ProgressBar::new(count);
@@ -95,6 +184,9 @@ impl<T: Write> ProgressBar<T> {
             self.bar_current_n = v[3].to_owned();
             self.bar_remain = v[4].to_owned();
             self.bar_end = v[5].to_owned();
+            if let Some(link) = &mut self.link {
+                link.reset();
+            }
         }
     }
 
@@ -119,17 +211,24 @@ impl<T: Write> ProgressBar<T> {
             .map(|x| x.to_owned())
             .filter(|x| !x.is_empty())
             .collect();
+        if let Some(link) = &mut self.link {
+            link.reset();
+        }
     }
 
     /// Create a new ProgressBar with default configuration
     /// but pass an arbitrary writer.
     ///
+    /// If the total is unknown, pass 0, and the bar is drawn as
+    /// a spinner that shows the current value without the bar,
+    /// the percent and the time left.
+    ///
     /// # Examples
     ///
     /// ```no_run
     /// use std::thread;
     /// use std::io::stderr;
-    /// use pbr::{ProgressBar, Units};
+    /// use pb::{ProgressBar, Units};
     ///
     /// let count = 1000;
     /// let mut pb = ProgressBar::on(stderr(), count);
@@ -145,6 +244,7 @@ impl<T: Write> ProgressBar<T> {
             total,
             current: 0,
             start_time: Instant::now(),
+            paused_at: None,
             units: Units::Default,
             is_finish: false,
             is_multibar: false,
@@ -166,6 +266,9 @@ impl<T: Write> ProgressBar<T> {
             message: String::new(),
             last_refresh_time: Instant::now(),
             max_refresh_rate: None,
+            last_line: String::new(),
+            parent: None,
+            link: None,
             handle,
         };
         pb.format(FORMAT);
@@ -190,7 +293,7 @@ impl<T: Write> ProgressBar<T> {
     /// # Examples
     ///
     /// ```no_run
-    /// use pbr::{ProgressBar, Units};
+    /// use pb::{ProgressBar, Units};
     ///
     /// let n_bytes = 100;
     /// let mut pb = ProgressBar::new(n_bytes);
@@ -213,13 +316,99 @@ impl<T: Write> ProgressBar<T> {
     pub fn set_max_refresh_rate(&mut self, w: Option<Duration>) {
         self.max_refresh_rate = w;
         if let Some(dur) = self.max_refresh_rate {
-            self.last_refresh_time = self.last_refresh_time - dur;
+            self.last_refresh_time -= dur;
         }
     }
 
     /// Resets the start time to now
     pub fn reset_start_time(&mut self) {
         self.start_time = Instant::now();
+        if self.paused_at.is_some() {
+            self.paused_at = Some(self.start_time);
+        }
+    }
+
+    /// Pause the clock of the bar, i.e. while waiting on user input.
+    /// The paused time is not counted in the elapsed time, so it
+    /// does not lower the speed, and the bar shows "paused" instead
+    /// of the time left until `resume` is called.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut pb = ProgressBar::new(...);
+    /// pb.inc();
+    /// pb.pause();
+    /// let answer = prompt("continue? ");
+    /// pb.resume();
+    /// ```
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            let now = Instant::now();
+            self.paused_at = Some(now);
+            self.render(now);
+        }
+    }
+
+    /// Resume the clock of the bar after `pause`.
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            let now = Instant::now();
+            self.start_time += now.saturating_duration_since(paused_at);
+            self.render(now);
+        }
+    }
+
+    /// Return whether the clock of the bar is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    // elapsed returns the time elapsed since the start at the given time,
+    // without the time since the bar was paused.
+    fn elapsed(&self, now: Instant) -> Duration {
+        self.paused_at
+            .unwrap_or(now)
+            .saturating_duration_since(self.start_time)
+    }
+
+    /// Save the state of the bar, see `restore`.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            current: self.current,
+            total: self.total,
+            elapsed: self.elapsed(Instant::now()),
+            message: self.message.clone(),
+            units: self.units,
+        }
+    }
+
+    /// Restore the state of the bar from a snapshot.
+    /// The elapsed time continues from the elapsed time of the snapshot,
+    /// so the speed and the time left are the same as when it was saved.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut pb = ProgressBar::new(total);
+    /// if let Some(snapshot) = load_checkpoint() {
+    ///     pb.restore(&snapshot);
+    /// }
+    /// for item in items.skip(pb.snapshot().current as usize) {
+    ///     // ...
+    ///     pb.inc();
+    ///     if checkpoint_due() {
+    ///         save_checkpoint(&pb.snapshot());
+    ///     }
+    /// }
+    /// ```
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        let now = self.paused_at.unwrap_or_else(Instant::now);
+        self.current = snapshot.current;
+        self.total = snapshot.total;
+        self.start_time = now.checked_sub(snapshot.elapsed).unwrap_or(now);
+        self.message(&snapshot.message);
+        self.units = snapshot.units;
     }
 
     /// Set message to display in the prefix,
@@ -246,8 +435,13 @@ impl<T: Write> ProgressBar<T> {
         self.message = message.replace(['\n', '\r'], " ")
     }
 
+    // handle returns the writer of the progress bar.
+    pub(crate) fn handle(&self) -> &T {
+        &self.handle
+    }
+
     /// Get terminal width, from configuration, terminal size, or default(80)
-    fn width(&mut self) -> usize {
+    pub(crate) fn width(&mut self) -> usize {
         if let Some(w) = self.width {
             w
         } else if let Some((Width(w), _)) = terminal_size() {
@@ -260,12 +454,106 @@ impl<T: Write> ProgressBar<T> {
     fn draw(&mut self) {
         let now = Instant::now();
         if let Some(mrr) = self.max_refresh_rate {
-            if now - self.last_refresh_time < mrr && self.current < self.total {
+            let last = self.current >= self.total && !self.is_spinner();
+            if now - self.last_refresh_time < mrr && !last {
                 return;
             }
         }
+        self.render(now);
+    }
+
+    // render draws the progress bar unconditionally,
+    // ignoring the max refresh rate.
+    fn render(&mut self, now: Instant) {
+        // the MultiBar renders its bars itself.
+        if let Some(mut link) = self.link.take() {
+            if link.style() != Some(self.style()) {
+                link.template(self.style(), self.detached());
+            }
+            link.update(self.current, self.total, self.tick_state, &self.message);
+            self.link = Some(link);
+        } else {
+            let out = self.line(now);
+            printfl!(self.handle, "{}", redraw_line(&self.last_line, &out));
+            self.last_line = out;
+        }
+
+        self.last_refresh_time = Instant::now();
+    }
+
+    // style returns the configuration of the bar, see `Style`.
+    fn style(&self) -> Style {
+        Style {
+            start_time: self.start_time,
+            paused_at: self.paused_at,
+            units: self.units,
+            width: self.width,
+            is_finish: self.is_finish,
+            show_bar: self.show_bar,
+            show_speed: self.show_speed,
+            show_percent: self.show_percent,
+            show_counter: self.show_counter,
+            show_time_left: self.show_time_left,
+            show_tick: self.show_tick,
+            show_message: self.show_message,
+        }
+    }
 
-        let mut time_elapsed = now - self.start_time;
+    // set_progress applies the progress received from the bar
+    // to its copy in the MultiBar.
+    pub(crate) fn set_progress(&mut self, progress: Progress) {
+        self.current = progress.current;
+        self.total = progress.total;
+        self.tick_state = progress.tick_state;
+        if let Some(message) = progress.message {
+            self.message = message;
+        }
+    }
+
+    // detached returns a copy of the bar without its writer,
+    // that can be rendered by the MultiBar.
+    fn detached(&self) -> ProgressBar<Sink> {
+        ProgressBar {
+            start_time: self.start_time,
+            paused_at: self.paused_at,
+            units: self.units,
+            total: self.total,
+            current: self.current,
+            bar_start: self.bar_start.clone(),
+            bar_current: self.bar_current.clone(),
+            bar_current_n: self.bar_current_n.clone(),
+            bar_remain: self.bar_remain.clone(),
+            bar_end: self.bar_end.clone(),
+            tick: self.tick.clone(),
+            tick_state: self.tick_state,
+            width: self.width,
+            message: self.message.clone(),
+            last_refresh_time: self.last_refresh_time,
+            max_refresh_rate: self.max_refresh_rate,
+            last_line: String::new(),
+            is_finish: self.is_finish,
+            is_multibar: self.is_multibar,
+            show_bar: self.show_bar,
+            show_speed: self.show_speed,
+            show_percent: self.show_percent,
+            show_counter: self.show_counter,
+            show_time_left: self.show_time_left,
+            show_tick: self.show_tick,
+            show_message: self.show_message,
+            parent: None,
+            link: None,
+            handle: io::sink(),
+        }
+    }
+
+    // is_spinner returns whether the total is unknown.
+    fn is_spinner(&self) -> bool {
+        self.total == 0
+    }
+
+    // line returns the progress bar line at the given time, without drawing it.
+    pub(crate) fn line(&mut self, now: Instant) -> String {
+        let mut time_elapsed = self.elapsed(now);
         if time_elapsed.is_zero() {
             time_elapsed = Duration::from_nanos(1);
         }
@@ -277,8 +565,9 @@ impl<T: Write> ProgressBar<T> {
         let mut base = String::new();
         let mut prefix = String::new();
         let mut suffix = String::from(" ");
+        let spinner = self.is_spinner();
         // precent box
-        if self.show_percent {
+        if self.show_percent && !spinner {
             let percent = self.current as f64 / (self.total as f64 / 100f64);
             parts.push(format!(
                 "{:.*} %",
@@ -296,7 +585,9 @@ impl<T: Write> ProgressBar<T> {
         }
 
         // time left box
-        if self.show_time_left && self.current > 0 && self.total > self.current {
+        if self.paused_at.is_some() {
+            parts.push("paused".to_string());
+        } else if self.show_time_left && self.current > 0 && self.total > self.current {
             let left = 1. / speed * (self.total - self.current) as f64;
             if left < 60. {
                 parts.push(format!("{:.0}s", left));
@@ -315,19 +606,21 @@ impl<T: Write> ProgressBar<T> {
         if self.show_counter {
             let (c, t) = (self.current as f64, self.total as f64);
             prefix = prefix
-                + &match self.units {
-                    Units::Default => format!("{} / {} ", c, t),
-                    Units::Bytes => format!("{} / {} ", kb_fmt!(c), kb_fmt!(t)),
+                + &match (self.units, spinner) {
+                    (Units::Default, false) => format!("{} / {} ", c, t),
+                    (Units::Bytes, false) => format!("{} / {} ", kb_fmt!(c), kb_fmt!(t)),
+                    (Units::Default, true) => format!("{} ", c),
+                    (Units::Bytes, true) => format!("{} ", kb_fmt!(c)),
                 };
         }
 
         // tick box
-        if self.show_tick {
+        if self.show_tick || spinner {
             prefix = prefix + &format!("{} ", self.tick[self.tick_state]);
         }
 
         // bar box
-        if self.show_bar {
+        if self.show_bar && !spinner {
             let p = prefix.chars().count() + suffix.chars().count() + 3;
             if p < width {
                 let size = width - p;
@@ -353,10 +646,7 @@ impl<T: Write> ProgressBar<T> {
             let gap = width - out.len();
             out = out + &" ".repeat(gap);
         }
-
-        printfl!(self.handle, "\r{}", out);
-
-        self.last_refresh_time = Instant::now();
+        out
     }
 
     // finish_draw ensure that the progress bar is reached to its end,
@@ -385,6 +675,10 @@ impl<T: Write> ProgressBar<T> {
     /// Call finish and write string `s` that will replace the progress bar.
     pub fn finish_print(&mut self, s: &str) {
         self.finish_draw();
+        if let Some(link) = &self.link {
+            link.text(s);
+            return self.finish();
+        }
         let width = self.width();
         let mut out = s.to_owned();
         if s.len() < width {
@@ -408,11 +702,92 @@ impl<T: Write> ProgressBar<T> {
         printfl!(self.handle, "\n{}", s);
     }
 
+    /// Print the line `s` above the progress bar and redraw the bar below it.
+    /// Useful for logging events while the bar is still running.
+    ///
+    /// If the ProgressBar is part of MultiBar instance,
+    /// the line is printed above all the bars of the MultiBar.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pb::ProgressBar;
+    ///
+    /// let mut pb = ProgressBar::new(10);
+    /// for i in 0..10 {
+    ///     if i % 5 == 0 {
+    ///         pb.println(&format!("reached {}", i));
+    ///     }
+    ///     pb.inc();
+    /// }
+    /// pb.finish();
+    /// ```
+    pub fn println(&mut self, s: &str) {
+        if let Some(link) = &self.link {
+            return link.above(s);
+        }
+        let width = self.width();
+        printfl!(self.handle, "\r{}\r{}\n", " ".repeat(width), s);
+        self.last_line.clear();
+        self.render(Instant::now());
+    }
+
+    /// Clear the progress bar, call `f`
+    /// and draw the bar again below whatever `f` printed.
+    /// Useful to prompt the user for input while the bar is running.
+    ///
+    /// If the ProgressBar is part of MultiBar instance, all the bars
+    /// of the MultiBar are cleared by `listen`, which does not draw
+    /// them until `f` returns. If `listen` is not running on another
+    /// thread, `f` is called as is.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io::stdin;
+    /// use pb::ProgressBar;
+    ///
+    /// let mut pb = ProgressBar::new(10);
+    /// pb.add(5);
+    /// let answer = pb.suspend(|| {
+    ///     let mut answer = String::new();
+    ///     println!("continue? [y/n]");
+    ///     stdin().read_line(&mut answer).unwrap();
+    ///     answer
+    /// });
+    /// if answer.trim() == "y" {
+    ///     pb.add(5);
+    /// }
+    /// pb.finish();
+    /// ```
+    pub fn suspend<F: FnOnce() -> R, R>(&mut self, f: F) -> R {
+        // the MultiBar owns the screen in multibar mode.
+        if let Some(link) = &self.link {
+            return link.suspend(|| suspended(f));
+        }
+        let width = self.width();
+        printfl!(self.handle, "\r{}\r", " ".repeat(width));
+        self.last_line.clear();
+        let ret = suspended(f);
+        self.render(Instant::now());
+        ret
+    }
+
     /// Calling finish manually will set current to
     /// total and draw the last time.
     pub fn finish(&mut self) {
         self.finish_draw();
-        self.handle.write(b"").expect("write() failed");
+        if let Some(link) = &mut self.link {
+            link.finish();
+        }
+        self.report();
+    }
+
+    // report reports the progress to the parent bar, if there is one.
+    fn report(&self) {
+        if let Some((node, i)) = &self.parent {
+            node.update(*i, self.current, self.total, self.is_finish);
+        }
     }
 
     /// Update progress bar even though no progress are
@@ -435,16 +810,17 @@ impl<T: Write> ProgressBar<T> {
     /// ```
     pub fn tick(&mut self) {
         self.tick_state = (self.tick_state + 1) % self.tick.len();
-        if self.current <= self.total {
+        if self.current <= self.total || self.is_spinner() {
             self.draw()
         }
+        self.report();
     }
 
     /// Manually set the current value of the bar.
     ///
     /// # Examples
     /// ```no_run
-    /// use pbr::ProgressBar;
+    /// use pb::ProgressBar;
     ///
     /// let mut pb = ProgressBar::new(10);
     /// pb.set(8);
@@ -460,7 +836,7 @@ impl<T: Write> ProgressBar<T> {
     /// # Examples
     ///
     /// ```no_run
-    /// use pbr::ProgressBar;
+    /// use pb::ProgressBar;
     ///
     /// let mut pb = ProgressBar::new(10);
     /// pb.add(5);
@@ -496,6 +872,16 @@ mod test {
     use crate::pb::{ProgressBar, Units};
     use std::time::Duration;
 
+    // bar returns a bar of 40 columns that draws to `out`, without
+    // the speed and the time left, which depend on the test timing.
+    fn bar(out: &mut Vec<u8>, total: u64) -> ProgressBar<&mut Vec<u8>> {
+        let mut pb = ProgressBar::on(out, total);
+        pb.show_speed = false;
+        pb.show_time_left = false;
+        pb.set_width(Some(40));
+        pb
+    }
+
     #[test]
     fn format() {
         let fmt = "[~> ]";
@@ -618,6 +1004,122 @@ mod test {
         );
     }
 
+    #[test]
+    fn println() {
+        let mut out = Vec::new();
+        let mut pb = bar(&mut out, 10);
+        pb.add(5);
+        pb.println("hello");
+        let out = std::str::from_utf8(&out).unwrap();
+        let bar = "\r5 / 10 [==========>-----------] 50.00 % ";
+        assert_eq!(
+            out,
+            format!("{}\r{}\rhello\n{}", bar, " ".repeat(40), bar),
+        );
+    }
+
+    #[test]
+    fn redraw_changed() {
+        let mut out = Vec::new();
+        let mut pb = bar(&mut out, 10);
+        pb.message("copy ");
+        pb.add(5);
+        pb.add(1);
+        let out = std::str::from_utf8(&out).unwrap();
+        // only the end of the line is written again.
+        assert!(out.starts_with("\rcopy 5 / 10 ["));
+        assert!(out.contains("\r\x1B[5C6 / 10 ["));
+        assert_eq!(out.matches("copy").count(), 1);
+    }
+
+    #[test]
+    fn suspend() {
+        let mut out = Vec::new();
+        let mut pb = bar(&mut out, 10);
+        pb.add(5);
+        assert_eq!(pb.suspend(|| 42), 42);
+        drop(pb);
+
+        // the bar is cleared, and drawn again as a whole after `f`.
+        let out = std::str::from_utf8(&out).unwrap();
+        let blank = format!("\r{}\r", " ".repeat(40));
+        let (before, after) = out.split_once(&blank).unwrap();
+        assert!(before.starts_with("\r5 / 10 ["));
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn spinner() {
+        let mut out = Vec::new();
+        let mut pb = ProgressBar::on(&mut out, 0);
+        pb.show_speed = false;
+        pb.tick_format("abc");
+        pb.set_width(Some(20));
+        pb.add(3);
+        pb.add(4);
+        pb.finish();
+        // the current value is followed by the tick,
+        // without the percent and the bar boxes.
+        let out = std::str::from_utf8(&out).unwrap();
+        assert!(out.starts_with("\r3 b "));
+        assert!(out.contains("\r7 c "));
+        assert!(!out.contains('%') && !out.contains('['));
+    }
+
+    #[test]
+    fn restore() {
+        let mut pb = ProgressBar::on(Vec::new(), 100);
+        pb.message("copy ");
+        pb.set_units(Units::Bytes);
+        pb.add(40);
+        pb.start_time -= Duration::from_secs(4);
+        let snapshot = pb.snapshot();
+        assert!(snapshot.elapsed >= Duration::from_secs(4));
+
+        let mut out = Vec::new();
+        let mut pb = bar(&mut out, 0);
+        pb.show_time_left = true;
+        pb.restore(&snapshot);
+        pb.add(0);
+        // the time left is computed from the restored elapsed time.
+        let out = std::str::from_utf8(&out).unwrap();
+        assert!(out.starts_with("\rcopy 40 B / 100 B "));
+        assert!(out.trim_end().ends_with(" 6s"));
+    }
+
+    #[test]
+    fn pause() {
+        let mut out = Vec::new();
+        let mut pb = bar(&mut out, 100);
+        pb.show_time_left = true;
+        pb.add(40);
+        pb.start_time -= Duration::from_secs(14);
+        pb.pause();
+        assert!(pb.is_paused());
+        // pretend the bar was paused for 10s.
+        pb.paused_at = pb.paused_at.map(|t| t - Duration::from_secs(10));
+        assert!(pb.snapshot().elapsed < Duration::from_secs(5));
+        pb.resume();
+        pb.add(0);
+        drop(pb);
+
+        let out = std::str::from_utf8(&out).unwrap();
+        assert!(out.contains(" paused"));
+        // the time left only counts the 4s the bar was running.
+        assert!(out.trim_end().ends_with(" 6s"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let mut pb = ProgressBar::on(Vec::new(), 10);
+        pb.add(3);
+        let snapshot = pb.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored: crate::Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, snapshot);
+    }
+
     #[test]
     fn kb_fmt() {
         let kb = 1024f64;
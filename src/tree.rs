@@ -0,0 +1,197 @@
+use crate::multi::INDENT;
+use crate::{MultiBar, Pipe, ProgressBar};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+// Child is the progress of a child bar, as seen by its parent.
+struct Child {
+    weight: f64,
+    current: u64,
+    total: u64,
+    done: bool,
+}
+
+impl Child {
+    // fraction returns the completed fraction of the child, between 0 and 1.
+    fn fraction(&self) -> f64 {
+        if self.done {
+            1.
+        } else if self.total == 0 {
+            0.
+        } else {
+            (self.current as f64 / self.total as f64).min(1.)
+        }
+    }
+}
+
+// Node is shared between a parent bar and its children,
+// the children report their progress to it.
+pub(crate) struct Node {
+    bar: Mutex<ProgressBar<Pipe>>,
+    children: Mutex<Vec<Child>>,
+}
+
+impl Node {
+    // update sets the progress of the child at index `i`,
+    // and updates the parent bar with the weighted aggregate of its children.
+    // The parent bar is finished once all its children are finished.
+    pub(crate) fn update(&self, i: usize, current: u64, total: u64, done: bool) {
+        let (fraction, all_done) = {
+            let mut children = self.children.lock().unwrap();
+            let child = &mut children[i];
+            child.current = current;
+            child.total = total;
+            child.done = done;
+
+            let weights: f64 = children.iter().map(|c| c.weight).sum();
+            let progress: f64 = children.iter().map(|c| c.weight * c.fraction()).sum();
+            let fraction = if weights > 0. { progress / weights } else { 0. };
+            (fraction, children.iter().all(|c| c.done))
+        };
+
+        let mut bar = self.bar.lock().unwrap();
+        if bar.is_finish {
+            return;
+        }
+        if all_done {
+            bar.finish();
+        } else {
+            let current = (fraction * bar.total as f64) as u64;
+            bar.set(current);
+        }
+    }
+}
+
+/// ParentBar is a bar in a `MultiBar` whose progress is
+/// the weighted aggregate of the progress of its children.
+/// Child bars are drawn indented below their parent,
+/// and the parent finishes once all its children are finished.
+///
+/// The ParentBar can be cloned and shared between threads.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::thread;
+/// use pb::MultiBar;
+///
+/// let mb = Arc::new(MultiBar::new());
+/// mb.spawn();
+///
+/// let workspace = mb.create_parent_bar(100);
+/// workspace.message("Build workspace ");
+/// for name in ["core", "cli", "web"] {
+///     let mut p = workspace.create_child(50);
+///     p.message(&format!("{} ", name));
+///     let _ = thread::spawn(move || {
+///         for _ in 0..50 {
+///             p.inc();
+///         }
+///         p.finish();
+///     });
+/// }
+///
+/// mb.join();
+/// ```
+#[derive(Clone)]
+pub struct ParentBar {
+    node: Arc<Node>,
+}
+
+impl<T: Write> MultiBar<T> {
+    /// create_parent_bar creates new `ParentBar` like `create_bar`.
+    /// Its progress is drawn relative to the given `total`.
+    pub fn create_parent_bar(&self, total: u64) -> ParentBar {
+        ParentBar::new(self.create_bar(total))
+    }
+}
+
+impl ParentBar {
+    fn new(bar: ProgressBar<Pipe>) -> ParentBar {
+        ParentBar {
+            node: Arc::new(Node {
+                bar: Mutex::new(bar),
+                children: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Create a child bar with weight 1.
+    pub fn create_child(&self, total: u64) -> ProgressBar<Pipe> {
+        self.create_child_weighted(total, 1.)
+    }
+
+    /// Create a child bar with the given `weight`,
+    /// i.e. a child with weight 2 moves the parent
+    /// twice as much as a child with weight 1.
+    pub fn create_child_weighted(&self, total: u64, weight: f64) -> ProgressBar<Pipe> {
+        let mut bar = self.node.bar.lock().unwrap();
+        let mut child = bar.handle().create_child(total);
+        child.set_width(Some(bar.width().saturating_sub(INDENT)));
+
+        let mut children = self.node.children.lock().unwrap();
+        child.parent = Some((self.node.clone(), children.len()));
+        children.push(Child {
+            weight,
+            current: 0,
+            total,
+            done: false,
+        });
+        drop(children);
+        drop(bar);
+
+        child.add(0);
+        child
+    }
+
+    /// Create a nested `ParentBar` as a child with weight 1.
+    pub fn create_parent(&self, total: u64) -> ParentBar {
+        ParentBar::new(self.create_child(total))
+    }
+
+    /// Set the message of the parent bar, see `ProgressBar::message`.
+    pub fn message(&self, message: &str) {
+        let mut bar = self.node.bar.lock().unwrap();
+        bar.message(message);
+        bar.tick();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::MultiBar;
+
+    #[test]
+    fn aggregate() {
+        let mb = MultiBar::on(Vec::new());
+        let parent = mb.create_parent_bar(100);
+        let mut c1 = parent.create_child(10);
+        let mut c2 = parent.create_child_weighted(10, 3.);
+        let nested = parent.create_parent(100);
+        let mut c3 = nested.create_child(10);
+
+        c1.add(10);
+        // (1 * 1 + 3 * 0 + 1 * 0) / 5
+        assert!(parent.node.bar.lock().unwrap().add(0) == 20);
+        c3.add(5);
+        // (1 * 1 + 3 * 0 + 1 * 0.5) / 5
+        assert!(parent.node.bar.lock().unwrap().add(0) == 30);
+
+        c1.finish();
+        c2.finish();
+        assert!(!parent.node.bar.lock().unwrap().is_finish);
+        c3.finish();
+        assert!(nested.node.bar.lock().unwrap().is_finish);
+        assert!(parent.node.bar.lock().unwrap().is_finish);
+
+        mb.listen();
+        let out = mb.output();
+        let last = out.rsplit(&crate::tty::move_cursor_up(5)).next().unwrap();
+        let indents: Vec<_> = last
+            .lines()
+            .map(|l| l.trim_start_matches('\r').len() - l.trim_start().len())
+            .collect();
+        assert_eq!(indents, vec![0, 2, 2, 2, 4]);
+    }
+}
@@ -1,4 +1,5 @@
-//! A simple utility for getting the size of a terminal, and moving `n` lines up.
+//! A simple utility for getting the size of a terminal, moving the cursor,
+//! and redrawing only the changed part of a line.
 //!
 //! Supports both Linux and Windows.
 //!
@@ -22,4 +23,43 @@ pub use self::wasi::*;
 #[cfg(windows)]
 mod windows;
 #[cfg(windows)]
-pub use self::windows::*;
\ No newline at end of file
+pub use self::windows::*;
+
+/// Return string that replaces the line `old` with `new`,
+/// rewriting only the part of the line that changed.
+/// The cursor must be on the line, and is left after `new`,
+/// or after `old` if it is longer.
+///
+/// On windows, the cursor is moved by the console API rather than
+/// by the returned string, so the whole line is rewritten.
+pub fn redraw_line(old: &str, new: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+    #[cfg(not(windows))]
+    let same = old.chars().zip(new.chars()).take_while(|(a, b)| a == b).count();
+    #[cfg(windows)]
+    let same = 0;
+    let pad = old.chars().count().saturating_sub(new.chars().count());
+    let mut out = String::from("\r");
+    #[cfg(not(windows))]
+    if same > 0 {
+        out += &move_cursor_right(same);
+    }
+    out.extend(new.chars().skip(same));
+    out += &" ".repeat(pad);
+    out
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::redraw_line;
+
+    #[test]
+    fn redraw() {
+        assert_eq!(redraw_line("1 / 10", "1 / 10"), "");
+        assert_eq!(redraw_line("", "1 / 10"), "\r1 / 10");
+        assert_eq!(redraw_line("1 / 10", "2 / 10"), "\r2 / 10");
+        assert_eq!(redraw_line("1 / 10", "1 / 9"), "\r\x1B[4C9 ");
+    }
+}
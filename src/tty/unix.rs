@@ -32,7 +32,7 @@ pub fn terminal_size() -> Option<(Width, Height)> {
         } else {
             0
         };
-        (rows as u16, cols as u16)
+        (rows, cols)
     };
 
     if rows > 0 && cols > 0 {
@@ -47,6 +47,11 @@ pub fn move_cursor_up(n: usize) -> String {
     format!("\x1B[{}A", n)
 }
 
+/// Return string that move the cursor `n` columns right.
+pub fn move_cursor_right(n: usize) -> String {
+    format!("\x1B[{}C", n)
+}
+
 #[cfg(not(target_os = "redox"))]
 #[test]
 /// Compare with the output of `stty size`
@@ -67,8 +72,8 @@ fn compare_with_stty() {
 
     // stdout is "rows cols"
     let mut data = stdout.split_whitespace();
-    let rows = u16::from_str_radix(data.next().unwrap(), 10).unwrap();
-    let cols = u16::from_str_radix(data.next().unwrap(), 10).unwrap();
+    let rows = data.next().unwrap().parse::<u16>().unwrap();
+    let cols = data.next().unwrap().parse::<u16>().unwrap();
     println!("{}", stdout);
     println!("{} {}", rows, cols);
 
@@ -8,4 +8,9 @@ pub fn terminal_size() -> Option<(Width, Height)> {
 /// This is inherited from unix and will work only when wasi executed on unix.
 pub fn move_cursor_up(n: usize) -> String {
     format!("\x1B[{}A", n)
+}
+
+/// This is inherited from unix and will work only when wasi executed on unix.
+pub fn move_cursor_right(n: usize) -> String {
+    format!("\x1B[{}C", n)
 }
\ No newline at end of file